@@ -6,6 +6,12 @@ use std::collections::HashSet;
 use rand::Rng;
 
 use rtt::util::rtt::vec_slist::{EmptyRandomTree, RandomTree, NodeRef};
+use rtt::util::grid::OccupancyGrid;
+use rtt::util::grid::traversal::Supercover;
+use rtt::util::grid::load::AsciiLegend;
+use rtt::util::path::Path;
+use rtt::util::path::shortcut::PathSimplifier;
+use rtt::util::goal::{GoalRegion, PointGoal};
 
 type Map<'a> = &'a [&'a [u8]];
 
@@ -26,13 +32,15 @@ fn main() {
           b"#      #      #",
           b"#             #",
           b"###############"];
-    let width = maze.iter().map(|line| line.len()).max().unwrap();
-    let height = maze.len();
-    let start = find_cell(b'*', maze, height, width).unwrap();
-    let finish = find_cell(b'@', maze, height, width).unwrap();
+    let grid = OccupancyGrid::from_ascii_lines(maze, &AsciiLegend::default()).unwrap();
+    let width = grid.cols();
+    let height = grid.rows();
+    let start = grid.start().unwrap();
+    let finish = grid.goal().unwrap();
 
     println!("Maze of {} rows and {} cols, start: {:?}, finish: {:?}", height, width, start, finish);
 
+    let goal = PointGoal::new(finish, 0.0, |a: &Coord, b: &Coord| manhattan(a, b) as f64);
     let mut rng = rand::thread_rng();
     let mut visited = HashSet::new();
     let mut iters = 0;
@@ -41,24 +49,29 @@ fn main() {
     let planner = rtt::PlannerInit::new(EmptyRandomTree::new());
     let planner = planner.add_root_ok(|empty_rtt: EmptyRandomTree<Coord>| Ok(empty_rtt.add_root(start)));
     let mut planner_node = planner.root_node_ok(|rtt: &mut RandomTree<Coord>| {
-        let root_ref = rtt.root();
         visited.insert(start);
-        Ok(RttNodeFocus { node_ref: root_ref, goal_reached: false, })
+        Ok(rtt.root())
     });
 
-    loop {
-        if planner_node.node_ref().goal_reached {
-            rev_path = planner_node.into_path_ok(
-                |rtt: RandomTree<_>, focus: RttNodeFocus| Ok(rtt.into_path(focus.node_ref))
-            );
-            break;
-        }
+    'planning: loop {
+        planner_node = match planner_node.goal_check_ok(goal.as_check(RandomTree::get_state)) {
+            rtt::PlannerGoalCheck::Reached(planner_goal) => {
+                rev_path = planner_goal.into_path_ok(|rtt: RandomTree<_>, node_ref| Ok(rtt.into_path(node_ref)));
+                break;
+            },
+            rtt::PlannerGoalCheck::NotReached(planner_node) =>
+                planner_node,
+        };
         let mut planner_ready_to_sample = planner_node.prepare_sample_ok(|_rtt: &mut _, _focus| Ok(()));
 
         loop {
             if iters >= 10000 {
-                println!("Planning limit reached");
-                return;
+                println!("Planning limit reached, moving toward the goal");
+                rev_path = planner_ready_to_sample.into_partial_path_ok(|rtt: RandomTree<Coord>| {
+                    let closest = rtt.closest_node(|coord| goal.distance(coord));
+                    Ok(rtt.into_path(closest))
+                });
+                break 'planning;
             }
             iters += 1;
 
@@ -69,19 +82,20 @@ fn main() {
 
             let route = {
                 let rtt = planner_closest.rtt();
-                let node_ref = &planner_closest.node_ref().node_ref;
+                let node_ref = planner_closest.node_ref();
                 let dst = planner_closest.sample();
-                StraightPathIter::new(rtt.get_state(node_ref), dst)
+                let src = rtt.get_state(node_ref);
+                if src == dst { None } else { Some(Supercover::new(src, dst).skip(1)) }
             };
 
             if let Some(path_iter) = route {
                 let blocked = path_iter.clone().any(|coord| {
-                    maze[coord.0][coord.1] == b'#' || visited.contains(&coord)
+                    grid.is_occupied(&coord) || visited.contains(&coord)
                 });
                 if !blocked {
                     planner_node =
                         planner_closest.has_transition_ok(
-                            |rtt: &mut _, focus: RttNodeFocus, _sample| perform_move(rtt, focus.node_ref, path_iter, &finish, &mut visited)
+                            |rtt: &mut _, node_ref, _sample| perform_move(rtt, node_ref, path_iter, &goal, &mut visited)
                         );
                     break;
                 }
@@ -91,28 +105,45 @@ fn main() {
         }
     }
 
-    let path: Vec<_> = rev_path.collect();
+    let mut route = Path::from(rev_path);
     println!("Path planned in {} iterations:", iters);
-    for row in 0 .. height {
-        for col in 0 .. min(width, maze[row].len()) {
-            print!("{}", if path.contains(&(row, col)) { '+' } else { maze[row][col] as char });
+    print_path(maze, width, &route.states().iter().cloned().collect());
+
+    let cell_distance = |a: &Coord, b: &Coord| {
+        ((a.0 as f64 - b.0 as f64).powi(2) + (a.1 as f64 - b.1 as f64).powi(2)).sqrt()
+    };
+    let report = PathSimplifier::new(&grid, cell_distance, || rng.gen::<f64>())
+        .simplify(route.states_mut(), 200);
+    println!(
+        "Path shortcut from {} to {} vertices, cost {:.1} -> {:.1}:",
+        report.vertices_before,
+        report.vertices_after,
+        report.cost_before,
+        report.cost_after,
+    );
+    let shortcut: HashSet<_> = route.states().windows(2)
+        .flat_map(|pair| Supercover::new(&pair[0], &pair[1]))
+        .collect();
+    print_path(maze, width, &shortcut);
+}
+
+fn print_path(maze: Map, width: usize, path: &HashSet<Coord>) {
+    for (row, line) in maze.iter().enumerate() {
+        for (col, &cell) in line.iter().enumerate().take(width) {
+            print!("{}", if path.contains(&(row, col)) { '+' } else { cell as char });
         }
-        println!("");
+        println!();
     }
 }
 
-type Coord = (usize, usize);
+type Coord = rtt::util::grid::Cell;
 
-struct RttNodeFocus {
-    node_ref: NodeRef,
-    goal_reached: bool,
+fn manhattan(coord_a: &Coord, coord_b: &Coord) -> usize {
+    (max(coord_a.0, coord_b.0) - min(coord_a.0, coord_b.0)) +
+        (max(coord_a.1, coord_b.1) - min(coord_a.1, coord_b.1))
 }
 
-fn locate_closest(rtt: &mut RandomTree<Coord>, sample: &Coord) -> Result<RttNodeFocus, rtt::util::NeverError> {
-    fn manhattan(coord_a: &Coord, coord_b: &Coord) -> usize {
-        (max(coord_a.0, coord_b.0) - min(coord_a.0, coord_b.0)) +
-            (max(coord_a.1, coord_b.1) - min(coord_a.1, coord_b.1))
-    }
+fn locate_closest(rtt: &mut RandomTree<Coord>, sample: &Coord) -> Result<NodeRef, rtt::util::NeverError> {
     let mut closest;
     {
         let states = rtt.states();
@@ -124,77 +155,26 @@ fn locate_closest(rtt: &mut RandomTree<Coord>, sample: &Coord) -> Result<RttNode
             }
         }
     }
-    Ok(RttNodeFocus { node_ref: closest.0, goal_reached: false, })
+    Ok(closest.0)
 }
 
-fn perform_move(
+fn perform_move<I, G>(
     rtt: &mut RandomTree<Coord>,
     mut node_ref: NodeRef,
-    path_iter: StraightPathIter,
-    finish: &Coord,
+    path_iter: I,
+    goal: &G,
     visited: &mut HashSet<Coord>,
 ) ->
-    Result<RttNodeFocus, rtt::util::NeverError>
+    Result<NodeRef, rtt::util::NeverError>
+    where I: Iterator<Item = Coord>,
+          G: GoalRegion<Coord>,
 {
-    let mut goal_reached = false;
     for coord in path_iter {
         node_ref = rtt.expand(node_ref, coord);
-        if &coord == finish {
-            goal_reached = true;
+        if goal.contains(&coord) {
             break;
         }
         visited.insert(coord);
     }
-    Ok(RttNodeFocus { node_ref, goal_reached, })
-}
-
-#[derive(Clone)]
-struct StraightPathIter {
-    source: Coord,
-    target: Coord,
-}
-
-impl StraightPathIter {
-    fn new(source: &Coord, target: &Coord) -> Option<StraightPathIter> {
-        if source == target {
-            None
-        } else if source.0 == target.0 || source.1 == target.1 {
-            Some(StraightPathIter {
-                source: source.clone(),
-                target: target.clone(),
-            })
-        } else {
-            None
-        }
-    }
-}
-
-impl Iterator for StraightPathIter {
-    type Item = Coord;
-
-    fn next(&mut self) -> Option<Self::Item> {
-        self.source = if self.target.0 < self.source.0 {
-            (self.source.0 - 1, self.source.1)
-        } else if self.target.0 > self.source.0 {
-            (self.source.0 + 1, self.source.1)
-        } else if self.target.1 < self.source.1 {
-            (self.source.0, self.source.1 - 1)
-        } else if self.target.1 > self.source.1 {
-            (self.source.0, self.source.1 + 1)
-        } else {
-            return None;
-        };
-        Some(self.source)
-    }
-}
-
-fn find_cell<'a>(cell: u8, map: Map<'a>, height: usize, width: usize) -> Option<Coord> {
-    for row in 0 .. height {
-        for col in 0 .. min(width, map[row].len()) {
-            if map[row][col] == cell {
-                return Some((row, col));
-            }
-        }
-    }
-    None
+    Ok(node_ref)
 }
@@ -0,0 +1,195 @@
+use std::time::{Duration, Instant};
+
+use super::state::{Problem, PlannerState};
+
+// Budget
+
+// Limits how much work a single `run` may do. An iteration is one drawn sample.
+#[derive(Clone, Copy, PartialEq, Default, Debug)]
+pub struct Budget {
+    pub iterations: Option<usize>,
+    pub time: Option<Duration>,
+}
+
+impl Budget {
+    pub fn iterations(iterations: usize) -> Budget {
+        Budget { iterations: Some(iterations), time: None, }
+    }
+
+    pub fn time(time: Duration) -> Budget {
+        Budget { iterations: None, time: Some(time), }
+    }
+
+    pub fn with_iterations(mut self, iterations: usize) -> Budget {
+        self.iterations = Some(iterations);
+        self
+    }
+
+    pub fn with_time(mut self, time: Duration) -> Budget {
+        self.time = Some(time);
+        self
+    }
+}
+
+pub trait GoalDistance: Problem {
+    fn distance_to_goal(&mut self, rtt: &Self::Rtt, node_ref: &Self::RttNodeRef) -> f64;
+}
+
+// Progress
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Progress<NR> {
+    pub iterations: usize,
+    pub best_node: Option<NR>,
+    pub best_distance: f64,
+}
+
+impl<NR> Default for Progress<NR> {
+    fn default() -> Progress<NR> {
+        Progress { iterations: 0, best_node: None, best_distance: f64::INFINITY, }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum RunStatus {
+    Done,
+    BudgetExhausted,
+}
+
+// IncrementalPlanner
+
+// the state is only taken out for the duration of a single step
+const LOST_STATE: &str = "planner state is lost after a panic inside `run`";
+
+// Keeps the partially grown tree between `run` calls, so each call continues the
+// exploration where the previous one stopped.
+pub struct IncrementalPlanner<P> where P: GoalDistance {
+    state: Option<PlannerState<P>>,
+    progress: Progress<P::RttNodeRef>,
+}
+
+// The best node ref is copied into the progress report.
+impl<P> IncrementalPlanner<P> where P: GoalDistance, P::RttNodeRef: Clone {
+    pub fn new(empty_rtt: P::EmptyRtt) -> IncrementalPlanner<P> {
+        IncrementalPlanner::from_state(PlannerState::new(empty_rtt))
+    }
+
+    pub fn from_state(state: PlannerState<P>) -> IncrementalPlanner<P> {
+        IncrementalPlanner { state: Some(state), progress: Progress::default(), }
+    }
+
+    pub fn state(&self) -> &PlannerState<P> {
+        self.state.as_ref().expect(LOST_STATE)
+    }
+
+    pub fn into_state(self) -> PlannerState<P> {
+        self.state.expect(LOST_STATE)
+    }
+
+    pub fn progress(&self) -> &Progress<P::RttNodeRef> {
+        &self.progress
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.state().is_done()
+    }
+
+    pub fn run(&mut self, problem: &mut P, budget: &Budget) -> Result<RunStatus, P::Error> {
+        let started_at = Instant::now();
+        let mut iterations = 0;
+        loop {
+            let mut state = self.state.take().expect(LOST_STATE);
+            if state.is_done() {
+                self.state = Some(state);
+                return Ok(RunStatus::Done);
+            }
+            let sampling = matches!(state, PlannerState::ReadyToSample(..));
+            if sampling {
+                let out_of_iterations = matches!(budget.iterations, Some(limit) if iterations >= limit);
+                let out_of_time = matches!(budget.time, Some(limit) if started_at.elapsed() >= limit);
+                if out_of_iterations || out_of_time {
+                    self.state = Some(state);
+                    return Ok(RunStatus::BudgetExhausted);
+                }
+            }
+            // a failed step leaves the planner where it was, so `run` may simply be retried
+            state = match state.step(problem) {
+                Ok(state) => state,
+                Err((state, error)) => {
+                    self.state = Some(state);
+                    return Err(error);
+                },
+            };
+            // only a sample actually drawn counts as an iteration
+            if sampling {
+                iterations += 1;
+                self.progress.iterations += 1;
+            }
+            if let PlannerState::RttNode(ref planner) = state {
+                let distance = problem.distance_to_goal(planner.rtt(), planner.node_ref());
+                if distance < self.progress.best_distance {
+                    self.progress.best_distance = distance;
+                    self.progress.best_node = Some(planner.node_ref().clone());
+                }
+            }
+            self.state = Some(state);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Budget, IncrementalPlanner, RunStatus};
+    use util::testing::IntegerLine;
+
+    fn odd(sample: i32) -> bool {
+        sample % 2 == 1
+    }
+
+    #[test]
+    fn frames() {
+        let mut problem = IntegerLine::new(Some(5)).with_reject(odd);
+        let mut planner = IncrementalPlanner::new(());
+        let budget = Budget::iterations(3);
+
+        let run = |planner: &mut IncrementalPlanner<IntegerLine>, problem: &mut IntegerLine| {
+            planner.run(problem, &budget).unwrap()
+        };
+
+        assert_eq!(run(&mut planner, &mut problem), RunStatus::BudgetExhausted);
+        assert_eq!(problem.drawn, 3);
+        assert_eq!(planner.progress().iterations, 3);
+        assert_eq!(planner.progress().best_node, Some(1));
+        assert_eq!(planner.progress().best_distance, 4.0);
+        assert_eq!(planner.state().rtt(), Some(&vec![0, 1]));
+
+        let mut frames = 1;
+        loop {
+            frames += 1;
+            if run(&mut planner, &mut problem) == RunStatus::Done {
+                break;
+            }
+        }
+        assert_eq!(frames, 4);
+        assert_eq!(problem.drawn, 10);
+        assert_eq!(planner.progress().best_distance, 0.0);
+        assert_eq!(planner.into_state().into_path(), Some(vec![0, 1, 2, 3, 4, 5]));
+    }
+
+    #[test]
+    fn resume_after_failure() {
+        let mut problem = IntegerLine::new(Some(3)).with_reject(odd).with_failing_draw(3);
+        let mut planner = IncrementalPlanner::new(());
+        let budget = Budget::iterations(10);
+
+        assert_eq!(planner.run(&mut problem, &budget), Err("sampler failure"));
+        assert_eq!(problem.drawn, 2);
+        assert_eq!(planner.progress().iterations, problem.drawn);
+        assert!(!planner.is_done());
+        assert_eq!(planner.state().rtt(), Some(&vec![0, 1]));
+
+        assert_eq!(planner.run(&mut problem, &budget), Ok(RunStatus::Done));
+        assert_eq!(planner.progress().iterations, problem.drawn);
+        assert_eq!(planner.into_state().into_path(), Some(vec![0, 1, 2, 3]));
+    }
+}
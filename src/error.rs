@@ -0,0 +1,192 @@
+use std::{fmt, error};
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Stage {
+    AddRoot,
+    RootNode,
+    GoalCheck,
+    PrepareSample,
+    Sample,
+    ClosestToSample,
+    HasTransition,
+    NoTransition,
+    IntoPath,
+    IntoPartialPath,
+}
+
+impl fmt::Display for Stage {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match *self {
+            Stage::AddRoot => "add_root",
+            Stage::RootNode => "root_node",
+            Stage::GoalCheck => "goal_check",
+            Stage::PrepareSample => "prepare_sample",
+            Stage::Sample => "sample",
+            Stage::ClosestToSample => "closest_to_sample",
+            Stage::HasTransition => "has_transition",
+            Stage::NoTransition => "no_transition",
+            Stage::IntoPath => "into_path",
+            Stage::IntoPartialPath => "into_partial_path",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+// PlannerError
+
+// Transition error tagged with the protocol stage and the planning iteration it happened on.
+#[derive(Clone, PartialEq, Debug)]
+pub struct PlannerError<E> {
+    stage: Stage,
+    iteration: usize,
+    error: E,
+}
+
+pub type DynPlannerError = PlannerError<DynError>;
+
+impl<E> PlannerError<E> {
+    pub fn new(stage: Stage, iteration: usize, error: E) -> PlannerError<E> {
+        PlannerError { stage, iteration, error, }
+    }
+
+    pub fn stage(&self) -> Stage {
+        self.stage
+    }
+
+    pub fn iteration(&self) -> usize {
+        self.iteration
+    }
+
+    pub fn error(&self) -> &E {
+        &self.error
+    }
+
+    pub fn into_error(self) -> E {
+        self.error
+    }
+
+    pub fn map<F, E2>(self, f: F) -> PlannerError<E2> where F: FnOnce(E) -> E2 {
+        PlannerError { stage: self.stage, iteration: self.iteration, error: f(self.error), }
+    }
+
+    // erases the underlying error type so errors of different transitions can be combined
+    pub fn boxed(self) -> DynPlannerError where E: error::Error + Send + Sync + 'static {
+        self.map(|error| DynError(Box::new(error)))
+    }
+}
+
+// The underlying error is left out, it is reported through `source`.
+impl<E> fmt::Display for PlannerError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "planner {} transition failed on iteration {}", self.stage, self.iteration)
+    }
+}
+
+impl<E> error::Error for PlannerError<E> where E: error::Error + 'static {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+// DynError
+
+// Type erased transition error, transparent for `Display` and `source`.
+#[derive(Debug)]
+pub struct DynError(pub Box<dyn error::Error + Send + Sync>);
+
+impl DynError {
+    pub fn get_ref(&self) -> &(dyn error::Error + Send + Sync + 'static) {
+        &*self.0
+    }
+
+    pub fn into_inner(self) -> Box<dyn error::Error + Send + Sync> {
+        self.0
+    }
+}
+
+impl fmt::Display for DynError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl error::Error for DynError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        self.0.source()
+    }
+}
+
+// WithStage
+
+pub trait WithStage<T, E> {
+    fn with_stage(self, stage: Stage, iteration: usize) -> Result<T, PlannerError<E>>;
+}
+
+impl<T, E> WithStage<T, E> for Result<T, E> {
+    fn with_stage(self, stage: Stage, iteration: usize) -> Result<T, PlannerError<E>> {
+        self.map_err(|error| PlannerError::new(stage, iteration, error))
+    }
+}
+
+pub trait WithStageRecoverable<T, P, E> {
+    fn with_stage_recoverable(self, stage: Stage, iteration: usize) -> Result<T, (P, PlannerError<E>)>;
+}
+
+impl<T, P, E> WithStageRecoverable<T, P, E> for Result<T, (P, E)> {
+    fn with_stage_recoverable(self, stage: Stage, iteration: usize) -> Result<T, (P, PlannerError<E>)> {
+        self.map_err(|(state, error)| (state, PlannerError::new(stage, iteration, error)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{io, error::Error};
+    use super::*;
+    use PlannerInit;
+
+    fn plan() -> Result<usize, DynPlannerError> {
+        let planner = PlannerInit::new(())
+            .add_root(|()| Ok::<_, io::Error>(0))
+            .with_stage(Stage::AddRoot, 0)
+            .map_err(PlannerError::boxed)?;
+        let mut planner_ready_to_sample = planner.root_node_ok(|_rtt: &mut _| Ok(()))
+            .prepare_sample_ok(|_rtt: &mut _, ()| Ok(()));
+        for iteration in 0 .. 10 {
+            let planner_sample = planner_ready_to_sample
+                .sample(|_rtt: &mut _| if iteration < 3 { Ok(iteration) } else { Err(fmt::Error) })
+                .with_stage(Stage::Sample, iteration)
+                .map_err(PlannerError::boxed)?;
+            planner_ready_to_sample = planner_sample.closest_to_sample_ok(|_rtt: &mut _, _sample: &_| Ok(()))
+                .no_transition_ok(|_rtt: &mut _, ()| Ok(()));
+        }
+        Ok(10)
+    }
+
+    #[test]
+    fn stage_tagged() {
+        let error = plan().unwrap_err();
+        assert_eq!(error.stage(), Stage::Sample);
+        assert_eq!(error.iteration(), 3);
+        assert_eq!(error.to_string(), "planner sample transition failed on iteration 3");
+        assert_eq!(error.source().unwrap().to_string(), fmt::Error.to_string());
+        assert!(error.error().get_ref().is::<fmt::Error>());
+
+        let planner_ready_to_sample = PlannerInit::new(())
+            .add_root_ok(|()| Ok(5))
+            .root_node_ok(|_rtt: &mut usize| Ok(()))
+            .prepare_sample_ok(|_rtt: &mut _, ()| Ok(()));
+        let (planner_ready_to_sample, error) = planner_ready_to_sample
+            .sample_recoverable(|_rtt: &mut usize| Err::<usize, _>(fmt::Error))
+            .with_stage_recoverable(Stage::Sample, 7)
+            .err().unwrap();
+        assert_eq!(planner_ready_to_sample.rtt(), &5);
+        assert_eq!((error.stage(), error.iteration()), (Stage::Sample, 7));
+
+        let (planner_ready_to_sample, error) = planner_ready_to_sample
+            .into_partial_path_recoverable(|rtt: usize| Err::<Vec<usize>, _>((rtt, fmt::Error)))
+            .with_stage_recoverable(Stage::IntoPartialPath, 7)
+            .err().unwrap();
+        assert_eq!(planner_ready_to_sample.rtt(), &5);
+        assert_eq!(error.to_string(), "planner into_partial_path transition failed on iteration 7");
+    }
+}
@@ -1,7 +1,16 @@
 pub mod util;
+pub mod error;
+pub mod state;
+pub mod budget;
+pub mod parallel;
+pub mod portfolio;
+
+// On error the planner state `P` is handed back along with the error, so no exploration is lost.
+pub type RecoverableResult<T, P, E> = Result<T, (P, E)>;
 
 // PlannerInit
 
+#[derive(Clone)]
 pub struct PlannerInit<ERT> {
     empty_rtt: ERT,
 }
@@ -28,6 +37,23 @@ impl<ERT, F, RT, E> TransAddRoot<ERT> for F where F: FnOnce(ERT) -> Result<RT, E
     }
 }
 
+// Hands the empty rtt back on failure, so the planner is restored without cloning it.
+pub trait TransAddRootRecoverable<ERT> {
+    type NonEmptyRtt;
+    type Error;
+
+    fn add_root_recoverable(self, empty_rtt: ERT) -> RecoverableResult<Self::NonEmptyRtt, ERT, Self::Error>;
+}
+
+impl<ERT, F, RT, E> TransAddRootRecoverable<ERT> for F where F: FnOnce(ERT) -> RecoverableResult<RT, ERT, E> {
+    type NonEmptyRtt = RT;
+    type Error = E;
+
+    fn add_root_recoverable(self, empty_rtt: ERT) -> RecoverableResult<Self::NonEmptyRtt, ERT, Self::Error> {
+        (self)(empty_rtt)
+    }
+}
+
 impl<ERT> PlannerInit<ERT> {
     pub fn add_root<TR>(self, trans: TR) ->
         Result<Planner<TR::NonEmptyRtt>, TR::Error>
@@ -40,12 +66,22 @@ impl<ERT> PlannerInit<ERT> {
         where TR: TransAddRoot<ERT, Error = util::NeverError>
     {
         self.add_root(trans)
-            .unwrap_or_else(|_: util::NeverError| unreachable!())
+            .unwrap_or_else(|never: util::NeverError| match never {})
+    }
+
+    pub fn add_root_recoverable<TR>(self, trans: TR) ->
+        RecoverableResult<Planner<TR::NonEmptyRtt>, Self, TR::Error>
+        where TR: TransAddRootRecoverable<ERT>
+    {
+        trans.add_root_recoverable(self.empty_rtt)
+            .map(|rtt| Planner { rtt, })
+            .map_err(|(empty_rtt, error)| (PlannerInit { empty_rtt, }, error))
     }
 }
 
 // Planner
 
+#[derive(Clone)]
 pub struct Planner<RT> {
     rtt: RT,
 }
@@ -81,12 +117,23 @@ impl<RT> Planner<RT> {
         where TR: TransRootNode<RT, Error = util::NeverError>
     {
         self.root_node(trans)
-            .unwrap_or_else(|_: util::NeverError| unreachable!())
+            .unwrap_or_else(|never: util::NeverError| match never {})
+    }
+
+    pub fn root_node_recoverable<TR>(mut self, trans: TR) ->
+        RecoverableResult<PlannerRttNode<RT, TR::RttNodeRef>, Self, TR::Error>
+        where TR: TransRootNode<RT>
+    {
+        match trans.root_node(&mut self.rtt) {
+            Ok(node_ref) => Ok(PlannerRttNode { rtt: self.rtt, node_ref, }),
+            Err(error) => Err((self, error)),
+        }
     }
 }
 
 // PlannerRttNode
 
+#[derive(Clone)]
 pub struct PlannerRttNode<RT, NR> {
     rtt: RT,
     node_ref: NR,
@@ -108,6 +155,23 @@ impl<RT, NR, F, P, E> TransIntoPath<RT, NR> for F where F: FnOnce(RT, NR) -> Res
     }
 }
 
+// Hands the rtt and the node back on failure, so the planner is restored without cloning the tree.
+pub trait TransIntoPathRecoverable<RT, NR> {
+    type RttPath;
+    type Error;
+
+    fn into_path_recoverable(self, rtt: RT, node_ref: NR) -> RecoverableResult<Self::RttPath, (RT, NR), Self::Error>;
+}
+
+impl<RT, NR, F, P, E> TransIntoPathRecoverable<RT, NR> for F where F: FnOnce(RT, NR) -> RecoverableResult<P, (RT, NR), E> {
+    type RttPath = P;
+    type Error = E;
+
+    fn into_path_recoverable(self, rtt: RT, node_ref: NR) -> RecoverableResult<Self::RttPath, (RT, NR), Self::Error> {
+        (self)(rtt, node_ref)
+    }
+}
+
 pub trait TransPrepareSample<RT, NR> {
     type Error;
 
@@ -122,6 +186,40 @@ impl<RT, NR, F, E> TransPrepareSample<RT, NR> for F where F: FnOnce(&mut RT, NR)
     }
 }
 
+// Hands the node back on failure.
+pub trait TransPrepareSampleRecoverable<RT, NR> {
+    type Error;
+
+    fn prepare_sample_recoverable(self, rtt: &mut RT, node_ref: NR) -> RecoverableResult<(), NR, Self::Error>;
+}
+
+impl<RT, NR, F, E> TransPrepareSampleRecoverable<RT, NR> for F where F: FnOnce(&mut RT, NR) -> RecoverableResult<(), NR, E> {
+    type Error = E;
+
+    fn prepare_sample_recoverable(self, rtt: &mut RT, node_ref: NR) -> RecoverableResult<(), NR, Self::Error> {
+        (self)(rtt, node_ref)
+    }
+}
+
+pub trait TransGoalCheck<RT, NR> {
+    type Error;
+
+    fn goal_check(self, rtt: &RT, node_ref: &NR) -> Result<bool, Self::Error>;
+}
+
+impl<RT, NR, F, E> TransGoalCheck<RT, NR> for F where F: FnOnce(&RT, &NR) -> Result<bool, E> {
+    type Error = E;
+
+    fn goal_check(self, rtt: &RT, node_ref: &NR) -> Result<bool, Self::Error> {
+        (self)(rtt, node_ref)
+    }
+}
+
+pub enum PlannerGoalCheck<RT, NR> {
+    Reached(PlannerGoalReached<RT, NR>),
+    NotReached(PlannerRttNode<RT, NR>),
+}
+
 impl<RT, NR> PlannerRttNode<RT, NR> {
     pub fn rtt(&self) -> &RT {
         &self.rtt
@@ -131,6 +229,30 @@ impl<RT, NR> PlannerRttNode<RT, NR> {
         &self.node_ref
     }
 
+    pub fn goal_check<TR>(self, trans: TR) -> Result<PlannerGoalCheck<RT, NR>, TR::Error>
+        where TR: TransGoalCheck<RT, NR>
+    {
+        self.goal_check_recoverable(trans).map_err(|(_, error)| error)
+    }
+
+    pub fn goal_check_ok<TR>(self, trans: TR) -> PlannerGoalCheck<RT, NR>
+        where TR: TransGoalCheck<RT, NR, Error = util::NeverError>
+    {
+        self.goal_check(trans)
+            .unwrap_or_else(|never: util::NeverError| match never {})
+    }
+
+    pub fn goal_check_recoverable<TR>(self, trans: TR) ->
+        RecoverableResult<PlannerGoalCheck<RT, NR>, Self, TR::Error>
+        where TR: TransGoalCheck<RT, NR>
+    {
+        match trans.goal_check(&self.rtt, &self.node_ref) {
+            Ok(true) => Ok(PlannerGoalCheck::Reached(PlannerGoalReached { rtt: self.rtt, node_ref: self.node_ref, })),
+            Ok(false) => Ok(PlannerGoalCheck::NotReached(self)),
+            Err(error) => Err((self, error)),
+        }
+    }
+
     pub fn into_path<TR>(self, trans: TR) -> Result<TR::RttPath, TR::Error>
         where TR: TransIntoPath<RT, NR>
     {
@@ -141,7 +263,14 @@ impl<RT, NR> PlannerRttNode<RT, NR> {
         where TR: TransIntoPath<RT, NR, Error = util::NeverError>
     {
         self.into_path(trans)
-            .unwrap_or_else(|_: util::NeverError| unreachable!())
+            .unwrap_or_else(|never: util::NeverError| match never {})
+    }
+
+    pub fn into_path_recoverable<TR>(self, trans: TR) -> RecoverableResult<TR::RttPath, Self, TR::Error>
+        where TR: TransIntoPathRecoverable<RT, NR>
+    {
+        trans.into_path_recoverable(self.rtt, self.node_ref)
+            .map_err(|((rtt, node_ref), error)| (PlannerRttNode { rtt, node_ref, }, error))
     }
 
     pub fn prepare_sample<TR>(mut self, trans: TR) -> Result<PlannerReadyToSample<RT>, TR::Error>
@@ -155,12 +284,66 @@ impl<RT, NR> PlannerRttNode<RT, NR> {
         where TR: TransPrepareSample<RT, NR, Error = util::NeverError>
     {
         self.prepare_sample(trans)
-            .unwrap_or_else(|_: util::NeverError| unreachable!())
+            .unwrap_or_else(|never: util::NeverError| match never {})
+    }
+
+    pub fn prepare_sample_recoverable<TR>(mut self, trans: TR) ->
+        RecoverableResult<PlannerReadyToSample<RT>, Self, TR::Error>
+        where TR: TransPrepareSampleRecoverable<RT, NR>
+    {
+        match trans.prepare_sample_recoverable(&mut self.rtt, self.node_ref) {
+            Ok(()) => Ok(PlannerReadyToSample { rtt: self.rtt, }),
+            Err((node_ref, error)) => Err((PlannerRttNode { rtt: self.rtt, node_ref, }, error)),
+        }
+    }
+}
+
+// PlannerGoalReached
+
+#[derive(Clone)]
+pub struct PlannerGoalReached<RT, NR> {
+    rtt: RT,
+    node_ref: NR,
+}
+
+impl<RT, NR> PlannerGoalReached<RT, NR> {
+    pub fn rtt(&self) -> &RT {
+        &self.rtt
+    }
+
+    pub fn node_ref(&self) -> &NR {
+        &self.node_ref
+    }
+
+    // keeps planning from the goal node, e.g. to look for a cheaper path
+    pub fn into_rtt_node(self) -> PlannerRttNode<RT, NR> {
+        PlannerRttNode { rtt: self.rtt, node_ref: self.node_ref, }
+    }
+
+    pub fn into_path<TR>(self, trans: TR) -> Result<TR::RttPath, TR::Error>
+        where TR: TransIntoPath<RT, NR>
+    {
+        trans.into_path(self.rtt, self.node_ref)
+    }
+
+    pub fn into_path_ok<TR>(self, trans: TR) -> TR::RttPath
+        where TR: TransIntoPath<RT, NR, Error = util::NeverError>
+    {
+        self.into_path(trans)
+            .unwrap_or_else(|never: util::NeverError| match never {})
+    }
+
+    pub fn into_path_recoverable<TR>(self, trans: TR) -> RecoverableResult<TR::RttPath, Self, TR::Error>
+        where TR: TransIntoPathRecoverable<RT, NR>
+    {
+        trans.into_path_recoverable(self.rtt, self.node_ref)
+            .map_err(|((rtt, node_ref), error)| (PlannerGoalReached { rtt, node_ref, }, error))
     }
 }
 
 // PlannerReadyToSample
 
+#[derive(Clone)]
 pub struct PlannerReadyToSample<RT> {
     rtt: RT,
 }
@@ -181,11 +364,64 @@ impl<RT, F, S, E> TransSample<RT> for F where F: FnOnce(&mut RT) -> Result<S, E>
     }
 }
 
+// Gives up sampling and builds the best path available so far, e.g. to the node closest to the goal.
+pub trait TransIntoPartialPath<RT> {
+    type RttPath;
+    type Error;
+
+    fn into_partial_path(self, rtt: RT) -> Result<Self::RttPath, Self::Error>;
+}
+
+impl<RT, F, P, E> TransIntoPartialPath<RT> for F where F: FnOnce(RT) -> Result<P, E> {
+    type RttPath = P;
+    type Error = E;
+
+    fn into_partial_path(self, rtt: RT) -> Result<Self::RttPath, Self::Error> {
+        (self)(rtt)
+    }
+}
+
+pub trait TransIntoPartialPathRecoverable<RT> {
+    type RttPath;
+    type Error;
+
+    fn into_partial_path_recoverable(self, rtt: RT) -> RecoverableResult<Self::RttPath, RT, Self::Error>;
+}
+
+impl<RT, F, P, E> TransIntoPartialPathRecoverable<RT> for F where F: FnOnce(RT) -> RecoverableResult<P, RT, E> {
+    type RttPath = P;
+    type Error = E;
+
+    fn into_partial_path_recoverable(self, rtt: RT) -> RecoverableResult<Self::RttPath, RT, Self::Error> {
+        (self)(rtt)
+    }
+}
+
 impl<RT> PlannerReadyToSample<RT> {
     pub fn rtt(&self) -> &RT {
         &self.rtt
     }
 
+    pub fn into_partial_path<TR>(self, trans: TR) -> Result<TR::RttPath, TR::Error>
+        where TR: TransIntoPartialPath<RT>
+    {
+        trans.into_partial_path(self.rtt)
+    }
+
+    pub fn into_partial_path_ok<TR>(self, trans: TR) -> TR::RttPath
+        where TR: TransIntoPartialPath<RT, Error = util::NeverError>
+    {
+        self.into_partial_path(trans)
+            .unwrap_or_else(|never: util::NeverError| match never {})
+    }
+
+    pub fn into_partial_path_recoverable<TR>(self, trans: TR) -> RecoverableResult<TR::RttPath, Self, TR::Error>
+        where TR: TransIntoPartialPathRecoverable<RT>
+    {
+        trans.into_partial_path_recoverable(self.rtt)
+            .map_err(|(rtt, error)| (PlannerReadyToSample { rtt, }, error))
+    }
+
     pub fn sample<TR>(mut self, trans: TR) ->
         Result<PlannerSample<RT, TR::Sample>, TR::Error>
         where TR: TransSample<RT>
@@ -198,12 +434,23 @@ impl<RT> PlannerReadyToSample<RT> {
         where TR: TransSample<RT, Error = util::NeverError>
     {
         self.sample(trans)
-            .unwrap_or_else(|_: util::NeverError| unreachable!())
+            .unwrap_or_else(|never: util::NeverError| match never {})
+    }
+
+    pub fn sample_recoverable<TR>(mut self, trans: TR) ->
+        RecoverableResult<PlannerSample<RT, TR::Sample>, Self, TR::Error>
+        where TR: TransSample<RT>
+    {
+        match trans.sample(&mut self.rtt) {
+            Ok(sample) => Ok(PlannerSample { rtt: self.rtt, sample, }),
+            Err(error) => Err((self, error)),
+        }
     }
 }
 
 // PlannerSample
 
+#[derive(Clone)]
 pub struct PlannerSample<RT, S> {
     rtt: RT,
     sample: S,
@@ -234,6 +481,27 @@ impl<RT, S> PlannerSample<RT, S> {
         &self.sample
     }
 
+    pub fn into_partial_path<TR>(self, trans: TR) -> Result<TR::RttPath, TR::Error>
+        where TR: TransIntoPartialPath<RT>
+    {
+        trans.into_partial_path(self.rtt)
+    }
+
+    pub fn into_partial_path_ok<TR>(self, trans: TR) -> TR::RttPath
+        where TR: TransIntoPartialPath<RT, Error = util::NeverError>
+    {
+        self.into_partial_path(trans)
+            .unwrap_or_else(|never: util::NeverError| match never {})
+    }
+
+    pub fn into_partial_path_recoverable<TR>(self, trans: TR) -> RecoverableResult<TR::RttPath, Self, TR::Error>
+        where TR: TransIntoPartialPathRecoverable<RT>
+    {
+        let sample = self.sample;
+        trans.into_partial_path_recoverable(self.rtt)
+            .map_err(|(rtt, error)| (PlannerSample { rtt, sample, }, error))
+    }
+
     pub fn closest_to_sample<TR>(mut self, trans: TR) ->
         Result<PlannerClosestNodeFound<RT, TR::RttNodeRef, S>, TR::Error>
         where TR: TransClosestToSample<RT, S>
@@ -246,12 +514,23 @@ impl<RT, S> PlannerSample<RT, S> {
         where TR: TransClosestToSample<RT, S, Error = util::NeverError>
     {
         self.closest_to_sample(trans)
-            .unwrap_or_else(|_: util::NeverError| unreachable!())
+            .unwrap_or_else(|never: util::NeverError| match never {})
+    }
+
+    pub fn closest_to_sample_recoverable<TR>(mut self, trans: TR) ->
+        RecoverableResult<PlannerClosestNodeFound<RT, TR::RttNodeRef, S>, Self, TR::Error>
+        where TR: TransClosestToSample<RT, S>
+    {
+        match trans.closest_to_sample(&mut self.rtt, &self.sample) {
+            Ok(node_ref) => Ok(PlannerClosestNodeFound { rtt: self.rtt, node_ref, sample: self.sample, }),
+            Err(error) => Err((self, error)),
+        }
     }
 }
 
 // PlannerClosestNodeFound
 
+#[derive(Clone)]
 pub struct PlannerClosestNodeFound<RT, NR, S> {
     rtt: RT,
     node_ref: NR,
@@ -288,6 +567,43 @@ impl<RT, NR, S, F, NRO, E> TransHasTransition<RT, NR, S> for F where F: FnOnce(&
     }
 }
 
+// Hands the node back on failure.
+pub trait TransNoTransitionRecoverable<RT, NR> {
+    type Error;
+
+    fn no_transition_recoverable(self, rtt: &mut RT, node_ref: NR) -> RecoverableResult<(), NR, Self::Error>;
+}
+
+impl<RT, NR, F, E> TransNoTransitionRecoverable<RT, NR> for F where F: FnOnce(&mut RT, NR) -> RecoverableResult<(), NR, E> {
+    type Error = E;
+
+    fn no_transition_recoverable(self, rtt: &mut RT, node_ref: NR) -> RecoverableResult<(), NR, Self::Error> {
+        (self)(rtt, node_ref)
+    }
+}
+
+// Hands the node and the sample back on failure.
+pub trait TransHasTransitionRecoverable<RT, NR, S> {
+    type RttNodeRef;
+    type Error;
+
+    fn has_transition_recoverable(self, rtt: &mut RT, node_ref: NR, sample: S) ->
+        RecoverableResult<Self::RttNodeRef, (NR, S), Self::Error>;
+}
+
+impl<RT, NR, S, F, NRO, E> TransHasTransitionRecoverable<RT, NR, S> for F
+    where F: FnOnce(&mut RT, NR, S) -> RecoverableResult<NRO, (NR, S), E>
+{
+    type RttNodeRef = NRO;
+    type Error = E;
+
+    fn has_transition_recoverable(self, rtt: &mut RT, node_ref: NR, sample: S) ->
+        RecoverableResult<Self::RttNodeRef, (NR, S), Self::Error>
+    {
+        (self)(rtt, node_ref, sample)
+    }
+}
+
 impl<RT, NR, S> PlannerClosestNodeFound<RT, NR, S> {
     pub fn rtt(&self) -> &RT {
         &self.rtt
@@ -301,6 +617,27 @@ impl<RT, NR, S> PlannerClosestNodeFound<RT, NR, S> {
         &self.sample
     }
 
+    pub fn into_partial_path<TR>(self, trans: TR) -> Result<TR::RttPath, TR::Error>
+        where TR: TransIntoPartialPath<RT>
+    {
+        trans.into_partial_path(self.rtt)
+    }
+
+    pub fn into_partial_path_ok<TR>(self, trans: TR) -> TR::RttPath
+        where TR: TransIntoPartialPath<RT, Error = util::NeverError>
+    {
+        self.into_partial_path(trans)
+            .unwrap_or_else(|never: util::NeverError| match never {})
+    }
+
+    pub fn into_partial_path_recoverable<TR>(self, trans: TR) -> RecoverableResult<TR::RttPath, Self, TR::Error>
+        where TR: TransIntoPartialPathRecoverable<RT>
+    {
+        let (node_ref, sample) = (self.node_ref, self.sample);
+        trans.into_partial_path_recoverable(self.rtt)
+            .map_err(|(rtt, error)| (PlannerClosestNodeFound { rtt, node_ref, sample, }, error))
+    }
+
     pub fn no_transition<TR>(mut self, trans: TR) -> Result<PlannerReadyToSample<RT>, TR::Error>
         where TR: TransNoTransition<RT, NR>
     {
@@ -312,7 +649,17 @@ impl<RT, NR, S> PlannerClosestNodeFound<RT, NR, S> {
         where TR: TransNoTransition<RT, NR, Error = util::NeverError>
     {
         self.no_transition(trans)
-            .unwrap_or_else(|_: util::NeverError| unreachable!())
+            .unwrap_or_else(|never: util::NeverError| match never {})
+    }
+
+    pub fn no_transition_recoverable<TR>(mut self, trans: TR) ->
+        RecoverableResult<PlannerReadyToSample<RT>, Self, TR::Error>
+        where TR: TransNoTransitionRecoverable<RT, NR>
+    {
+        match trans.no_transition_recoverable(&mut self.rtt, self.node_ref) {
+            Ok(()) => Ok(PlannerReadyToSample { rtt: self.rtt, }),
+            Err((node_ref, error)) => Err((PlannerClosestNodeFound { rtt: self.rtt, node_ref, sample: self.sample, }, error)),
+        }
     }
 
     pub fn has_transition<TR>(mut self, trans: TR) -> Result<PlannerRttNode<RT, TR::RttNodeRef>, TR::Error>
@@ -326,7 +673,19 @@ impl<RT, NR, S> PlannerClosestNodeFound<RT, NR, S> {
         where TR: TransHasTransition<RT, NR, S, Error = util::NeverError>
     {
         self.has_transition(trans)
-            .unwrap_or_else(|_: util::NeverError| unreachable!())
+            .unwrap_or_else(|never: util::NeverError| match never {})
+    }
+
+    // the transition gets the rtt by mutable reference, so on error the returned planner
+    // holds the rtt in whatever state the transition has left it, possibly already expanded
+    pub fn has_transition_recoverable<TR>(mut self, trans: TR) ->
+        RecoverableResult<PlannerRttNode<RT, TR::RttNodeRef>, Self, TR::Error>
+        where TR: TransHasTransitionRecoverable<RT, NR, S>
+    {
+        match trans.has_transition_recoverable(&mut self.rtt, self.node_ref, self.sample) {
+            Ok(node_ref) => Ok(PlannerRttNode { rtt: self.rtt, node_ref, }),
+            Err(((node_ref, sample), error)) => Err((PlannerClosestNodeFound { rtt: self.rtt, node_ref, sample, }, error)),
+        }
     }
 }
 
@@ -414,4 +773,99 @@ mod tests {
         });
         assert_eq!(path, sample_counter / 2 + 1);
     }
+
+    #[test]
+    fn unit_error_compat() {
+        fn legacy_root(rtt: &mut [usize]) -> Result<usize, ()> {
+            Ok(rtt.len())
+        }
+
+        let planner = PlannerInit::new(vec![7]).add_root_ok(Ok);
+        let planner_node = planner.root_node_ok(|rtt: &mut Vec<usize>| util::from_unit_error(legacy_root(rtt)));
+        assert_eq!(planner_node.node_ref(), &1);
+    }
+
+    #[test]
+    fn recoverable() {
+        let planner = PlannerInit::new(0)
+            .add_root_recoverable(|empty_rtt: usize| Err::<Vec<usize>, _>((empty_rtt, "collision service timeout")))
+            .err().unwrap().0
+            .add_root_ok(|rtt: usize| Ok(vec![rtt]));
+        let planner = planner.root_node_recoverable(|_rtt: &mut Vec<usize>| Err::<usize, _>(()))
+            .err().unwrap().0
+            .root_node_ok(|_rtt: &mut Vec<usize>| Ok(0));
+
+        let mut planner_ready_to_sample = planner.prepare_sample_ok(|_rtt: &mut _, _node_ref| Ok(()));
+        let mut attempts = 0;
+        let planner_sample = loop {
+            let result = planner_ready_to_sample.sample_recoverable(|rtt: &mut Vec<usize>| {
+                attempts += 1;
+                if attempts < 3 { Err(rtt.len()) } else { Ok(attempts) }
+            });
+            match result {
+                Ok(planner_sample) =>
+                    break planner_sample,
+                Err((planner, tree_size)) => {
+                    assert_eq!(tree_size, 1);
+                    planner_ready_to_sample = planner;
+                },
+            }
+        };
+        assert_eq!(planner_sample.sample(), &3);
+
+        let planner_sample = planner_sample.closest_to_sample_recoverable(|_rtt: &mut _, _sample: &_| Err::<usize, _>(()))
+            .err().unwrap().0;
+        let planner_closest = planner_sample.closest_to_sample_ok(|_rtt: &mut _, _sample: &_| Ok(0));
+        let (planner_closest, ()) = planner_closest
+            .has_transition_recoverable(|rtt: &mut Vec<usize>, node_ref: usize, sample: usize| {
+                rtt.push(sample);
+                Err::<usize, _>(((node_ref, sample), ()))
+            })
+            .err().unwrap();
+        assert_eq!(planner_closest.rtt(), &vec![0, 3]);
+        assert_eq!(planner_closest.node_ref(), &0);
+        let planner_node = planner_closest.has_transition_ok(|rtt: &mut Vec<usize>, _node_ref, sample| {
+            rtt.push(sample);
+            Ok(rtt.len() - 1)
+        });
+        let planner_node = planner_node
+            .into_path_recoverable(|rtt: Vec<usize>, node_ref: usize| Err::<Vec<usize>, _>(((rtt, node_ref), ())))
+            .err().unwrap().0;
+        assert_eq!(planner_node.into_path_ok(|rtt: Vec<usize>, node_ref: usize| Ok(rtt[node_ref])), 3);
+    }
+
+    #[test]
+    fn partial_path() {
+        use util::path::Path;
+        use util::rtt::vec_slist::{EmptyRandomTree, RandomTree};
+
+        let goal = 10;
+        let planner = PlannerInit::new(EmptyRandomTree::new())
+            .add_root_ok(|empty_rtt: EmptyRandomTree<i32>| Ok(empty_rtt.add_root(0)));
+        let mut planner_node = planner.root_node_ok(|rtt: &mut RandomTree<i32>| Ok(rtt.root()));
+        for &state in &[4, -2, 7] {
+            let planner_closest = planner_node
+                .prepare_sample_ok(|_rtt: &mut _, _node_ref| Ok(()))
+                .sample_ok(|_rtt: &mut _| Ok(state))
+                .closest_to_sample_ok(|rtt: &mut RandomTree<i32>, sample: &i32| {
+                    Ok(rtt.closest_node(|node_state| f64::from((node_state - sample).abs())))
+                });
+            planner_node = planner_closest.has_transition_ok(|rtt: &mut RandomTree<i32>, node_ref, sample| {
+                Ok(rtt.expand(node_ref, sample))
+            });
+        }
+
+        let planner_sample = planner_node
+            .prepare_sample_ok(|_rtt: &mut _, _node_ref| Ok(()))
+            .sample_ok(|_rtt: &mut _| Ok(100));
+        let (planner_sample, ()) = planner_sample
+            .into_partial_path_recoverable(|rtt: RandomTree<i32>| Err::<Path<i32>, _>((rtt, ())))
+            .err().unwrap();
+        assert_eq!(planner_sample.sample(), &100);
+        let path = planner_sample.into_partial_path_ok(|rtt: RandomTree<i32>| {
+            let closest = rtt.closest_node(|state| f64::from((state - goal).abs()));
+            Ok(rtt.forward_path(&closest))
+        });
+        assert_eq!(path.states(), &[0, 4, 7]);
+    }
 }
@@ -0,0 +1,240 @@
+use std::thread;
+
+use super::{PlannerReadyToSample, PlannerRttNode, RecoverableResult};
+use util::sample::Sampler;
+
+// Read-only part of an extension round, evaluated concurrently against a shared tree.
+pub trait Extender<RT, S>: Sync {
+    type RttNodeRef: Send;
+    type Extension: Send;
+
+    fn closest(&self, rtt: &RT, sample: &S) -> Self::RttNodeRef;
+
+    // steering and collision checking, `None` for no transition
+    fn extend(&self, rtt: &RT, node_ref: &Self::RttNodeRef, sample: &S) -> Option<Self::Extension>;
+}
+
+pub struct Candidate<S, NR, X> {
+    pub sample: S,
+    pub node_ref: NR,
+    pub extension: Option<X>,
+}
+
+// Evaluates every sample on up to `threads` workers. Each worker takes a contiguous
+// chunk, so candidates come back in sample order regardless of scheduling.
+pub fn evaluate<RT, S, EX>(rtt: &RT, samples: Vec<S>, extender: &EX, threads: usize) ->
+    Vec<Candidate<S, EX::RttNodeRef, EX::Extension>>
+    where RT: Sync,
+          S: Send + Sync,
+          EX: Extender<RT, S>,
+{
+    let evaluate_one = |sample: S| {
+        let node_ref = extender.closest(rtt, &sample);
+        let extension = extender.extend(rtt, &node_ref, &sample);
+        Candidate { sample, node_ref, extension, }
+    };
+    if threads <= 1 || samples.len() <= 1 {
+        return samples.into_iter().map(evaluate_one).collect();
+    }
+
+    let chunk_size = (samples.len() - 1) / threads + 1;
+    let mut chunks = Vec::new();
+    let mut samples = samples.into_iter();
+    loop {
+        let chunk: Vec<_> = samples.by_ref().take(chunk_size).collect();
+        if chunk.is_empty() {
+            break;
+        }
+        chunks.push(chunk);
+    }
+    thread::scope(|scope| {
+        let workers: Vec<_> = chunks.into_iter()
+            .map(|chunk| scope.spawn(move || chunk.into_iter().map(evaluate_one).collect::<Vec<_>>()))
+            .collect();
+        workers.into_iter()
+            .flat_map(|worker| worker.join().unwrap_or_else(|panic| ::std::panic::resume_unwind(panic)))
+            .collect()
+    })
+}
+
+pub enum BatchOutcome<RT, NR> {
+    // every candidate committed, goal not reached yet
+    Exhausted(PlannerReadyToSample<RT>),
+    // goal reached, the rest of the batch is discarded
+    GoalReached(PlannerRttNode<RT, NR>),
+}
+
+// ParallelExtension
+
+pub struct ParallelExtension<'a, EX: 'a> {
+    extender: &'a EX,
+    threads: usize,
+    batch_size: usize,
+}
+
+impl<'a, EX> ParallelExtension<'a, EX> {
+    pub fn new(extender: &'a EX) -> ParallelExtension<'a, EX> {
+        let threads = thread::available_parallelism().map(|count| count.get()).unwrap_or(1);
+        ParallelExtension { extender, threads, batch_size: threads * 8, }
+    }
+
+    pub fn threads(mut self, threads: usize) -> ParallelExtension<'a, EX> {
+        self.threads = threads.max(1);
+        self
+    }
+
+    pub fn batch_size(mut self, batch_size: usize) -> ParallelExtension<'a, EX> {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    // Draws a batch of samples, evaluates it in parallel against the tree as it was
+    // before the batch, then walks the protocol for every candidate in sample order:
+    // `commit` performs the `has_transition` expansion and `goal_reached` is checked
+    // for every new node. The result depends only on the sampler and the extender.
+    // A failed `commit` hands the node ref back, the planner is then returned with
+    // the tree as it was left and the rest of the batch is discarded.
+    pub fn run_batch<RT, SM, NR, C, G, E>(
+        &self,
+        planner: PlannerReadyToSample<RT>,
+        sampler: &mut SM,
+        mut commit: C,
+        mut goal_reached: G,
+    ) ->
+        RecoverableResult<BatchOutcome<RT, NR>, PlannerReadyToSample<RT>, E>
+        where RT: Sync,
+              SM: Sampler,
+              SM::Sample: Send + Sync,
+              EX: Extender<RT, SM::Sample>,
+              C: FnMut(&mut RT, EX::RttNodeRef, EX::Extension) -> RecoverableResult<NR, EX::RttNodeRef, E>,
+              G: FnMut(&RT, &NR) -> bool,
+    {
+        let samples: Vec<_> = (0 .. self.batch_size).map(|_| sampler.sample()).collect();
+        let candidates = evaluate(planner.rtt(), samples, self.extender, self.threads);
+
+        let mut planner_ready_to_sample = planner;
+        for Candidate { sample, node_ref, extension, } in candidates {
+            let planner_closest = planner_ready_to_sample
+                .sample_ok(|_rtt: &mut _| Ok(sample))
+                .closest_to_sample_ok(|_rtt: &mut _, _sample: &_| Ok(node_ref));
+            planner_ready_to_sample = match extension {
+                None =>
+                    planner_closest.no_transition_ok(|_rtt: &mut _, _node_ref| Ok(())),
+                Some(extension) => {
+                    let planner_node = planner_closest
+                        .has_transition_recoverable(|rtt: &mut _, node_ref, sample| {
+                            commit(rtt, node_ref, extension)
+                                .map_err(|(node_ref, error)| ((node_ref, sample), error))
+                        })
+                        .map_err(|(planner_closest, error)| {
+                            (planner_closest.no_transition_ok(|_rtt: &mut _, _node_ref| Ok(())), error)
+                        })?;
+                    if goal_reached(planner_node.rtt(), planner_node.node_ref()) {
+                        return Ok(BatchOutcome::GoalReached(planner_node));
+                    }
+                    planner_node.prepare_sample_ok(|_rtt: &mut _, _node_ref| Ok(()))
+                },
+            };
+        }
+        Ok(BatchOutcome::Exhausted(planner_ready_to_sample))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Extender, ParallelExtension, BatchOutcome};
+    use PlannerInit;
+    use util::NeverError;
+    use util::geom2d::{ObstacleWorld, Aabb, Shape};
+    use util::rtt::vec_slist::{EmptyRandomTree, RandomTree, NodeRef};
+    use util::sample::quasi::{Halton, QuasiRandomSampler};
+    use util::space::{Bounds, euclidean_distance};
+
+    struct Steer {
+        world: ObstacleWorld,
+        step: f64,
+    }
+
+    impl Extender<RandomTree<Vec<f64>>, Vec<f64>> for Steer {
+        type RttNodeRef = NodeRef;
+        type Extension = Vec<f64>;
+
+        fn closest(&self, rtt: &RandomTree<Vec<f64>>, sample: &Vec<f64>) -> NodeRef {
+            rtt.closest_node(|state| euclidean_distance(state, sample))
+        }
+
+        fn extend(&self, rtt: &RandomTree<Vec<f64>>, node_ref: &NodeRef, sample: &Vec<f64>) -> Option<Vec<f64>> {
+            let from = rtt.get_state(node_ref);
+            let distance = euclidean_distance(from, sample);
+            let t = if distance > self.step { self.step / distance } else { 1.0 };
+            let to = vec![from[0] + (sample[0] - from[0]) * t, from[1] + (sample[1] - from[1]) * t];
+            if self.world.is_segment_free(&[from[0], from[1]], &[to[0], to[1]]) { Some(to) } else { None }
+        }
+    }
+
+    fn plan(threads: usize) -> Vec<Vec<f64>> {
+        let mut world = ObstacleWorld::new(Aabb::new([0.0, 0.0], [1.0, 1.0]), 0.1);
+        world.add_obstacle(Shape::Box(Aabb::new([0.4, 0.0], [0.6, 0.8])));
+        let steer = Steer { world, step: 0.1, };
+        let goal = [0.9, 0.1];
+
+        let mut sampler = QuasiRandomSampler::new(Halton::with_dimension(2), Bounds::new(vec![0.0, 0.0], vec![1.0, 1.0]));
+        let extension = ParallelExtension::new(&steer).threads(threads).batch_size(16);
+        let planner = PlannerInit::new(EmptyRandomTree::new())
+            .add_root_ok(|empty_rtt: EmptyRandomTree<Vec<f64>>| Ok(empty_rtt.add_root(vec![0.1, 0.1])));
+        let mut planner_ready_to_sample = planner
+            .root_node_ok(|rtt: &mut RandomTree<Vec<f64>>| Ok(rtt.root()))
+            .prepare_sample_ok(|_rtt: &mut _, _node_ref| Ok(()));
+        for _ in 0 .. 200 {
+            let outcome = extension.run_batch(
+                planner_ready_to_sample,
+                &mut sampler,
+                |rtt: &mut RandomTree<Vec<f64>>, node_ref, state| Ok::<_, (_, NeverError)>(rtt.expand(node_ref, state)),
+                |rtt, node_ref| euclidean_distance(rtt.get_state(node_ref), &goal) < 0.1,
+            );
+            match outcome.unwrap_or_else(|(_, never)| match never {}) {
+                BatchOutcome::Exhausted(planner) =>
+                    planner_ready_to_sample = planner,
+                BatchOutcome::GoalReached(planner_node) =>
+                    return planner_node.into_path_ok(|rtt: RandomTree<_>, node_ref| Ok(rtt.forward_path(&node_ref).into_states())),
+            }
+        }
+        panic!("goal is not reached")
+    }
+
+    #[test]
+    fn failed_commit_keeps_tree() {
+        let steer = Steer { world: ObstacleWorld::new(Aabb::new([0.0, 0.0], [1.0, 1.0]), 0.1), step: 0.1, };
+        let mut sampler = QuasiRandomSampler::new(Halton::with_dimension(2), Bounds::new(vec![0.0, 0.0], vec![1.0, 1.0]));
+        let planner_ready_to_sample = PlannerInit::new(EmptyRandomTree::new())
+            .add_root_ok(|empty_rtt: EmptyRandomTree<Vec<f64>>| Ok(empty_rtt.add_root(vec![0.5, 0.5])))
+            .root_node_ok(|rtt: &mut RandomTree<Vec<f64>>| Ok(rtt.root()))
+            .prepare_sample_ok(|_rtt: &mut _, _node_ref| Ok(()));
+        let mut commits = 0;
+        let outcome = ParallelExtension::new(&steer).threads(2).batch_size(8).run_batch(
+            planner_ready_to_sample,
+            &mut sampler,
+            |rtt: &mut RandomTree<Vec<f64>>, node_ref, state| {
+                commits += 1;
+                if commits == 3 { Err((node_ref, "commit failure")) } else { Ok(rtt.expand(node_ref, state)) }
+            },
+            |_rtt, _node_ref| false,
+        );
+        let (planner_ready_to_sample, error) = match outcome {
+            Err(failure) => failure,
+            Ok(..) => panic!("commit should fail"),
+        };
+        assert_eq!(error, "commit failure");
+        assert_eq!(commits, 3);
+        assert_eq!(planner_ready_to_sample.rtt().states().children.count(), 2);
+    }
+
+    #[test]
+    fn deterministic_across_threads() {
+        let path = plan(1);
+        assert!(euclidean_distance(path.last().unwrap(), &[0.9, 0.1]) < 0.1);
+        assert!(path.iter().any(|state| state[1] > 0.8));
+        assert_eq!(plan(4), path);
+        assert_eq!(plan(3), path);
+    }
+}
@@ -0,0 +1,211 @@
+use std::thread;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
+
+use budget::{Budget, GoalDistance, IncrementalPlanner, RunStatus};
+
+// Cancellation
+
+// Shared flag checked by portfolio members between their own iterations.
+#[derive(Clone, Default, Debug)]
+pub struct Cancellation {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl Cancellation {
+    pub fn new() -> Cancellation {
+        Cancellation::default()
+    }
+
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+}
+
+// What a single portfolio member reports back, `path` is `None` if it gave up or was cancelled.
+pub struct Attempt<P> {
+    pub path: Option<P>,
+    pub iterations: usize,
+}
+
+// Lets members with different strategies share one `race` call.
+pub type BoxedMember<'a, P> = Box<dyn FnOnce(usize, &Cancellation) -> Attempt<P> + Send + 'a>;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Selection {
+    // the first path found wins, the others are cancelled
+    First,
+    // every member runs to completion, the cheapest path wins
+    Best,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct PlannerStats {
+    pub iterations: usize,
+    pub elapsed: Duration,
+    pub solved: bool,
+    pub cost: Option<f64>,
+}
+
+pub struct PortfolioResult<P> {
+    pub winner: Option<usize>,
+    pub path: Option<P>,
+    // indexed the same way as the planners passed to `race`
+    pub stats: Vec<PlannerStats>,
+}
+
+// Runs every planner on its own thread. Members are handed their index, which is
+// meant to pick a seed or a strategy.
+pub fn race<P, F, C>(planners: Vec<F>, selection: Selection, mut cost: C) -> PortfolioResult<P>
+    where F: FnOnce(usize, &Cancellation) -> Attempt<P> + Send,
+          P: Send,
+          C: FnMut(&P) -> f64,
+{
+    let cancellation = Cancellation::new();
+    let started_at = Instant::now();
+    let mut stats: Vec<_> = planners.iter()
+        .map(|_| PlannerStats { iterations: 0, elapsed: Duration::from_secs(0), solved: false, cost: None, })
+        .collect();
+    let mut winner: Option<(usize, P, f64)> = None;
+
+    thread::scope(|scope| {
+        let (tx, rx) = mpsc::channel();
+        for (index, planner) in planners.into_iter().enumerate() {
+            let tx = tx.clone();
+            let cancellation = cancellation.clone();
+            scope.spawn(move || {
+                let attempt = planner(index, &cancellation);
+                // the receiver lives until every member reports
+                tx.send((index, attempt, started_at.elapsed())).ok();
+            });
+        }
+        drop(tx);
+
+        for (index, attempt, elapsed) in rx {
+            let member = &mut stats[index];
+            member.iterations = attempt.iterations;
+            member.elapsed = elapsed;
+            if let Some(path) = attempt.path {
+                let path_cost = cost(&path);
+                member.solved = true;
+                member.cost = Some(path_cost);
+                let better = match winner {
+                    None => true,
+                    Some(..) if selection == Selection::First => false,
+                    Some((_, _, best_cost)) => path_cost < best_cost,
+                };
+                if better {
+                    winner = Some((index, path, path_cost));
+                }
+                if selection == Selection::First {
+                    cancellation.cancel();
+                }
+            }
+        }
+    });
+
+    match winner {
+        Some((index, path, _)) =>
+            PortfolioResult { winner: Some(index), path: Some(path), stats, },
+        None =>
+            PortfolioResult { winner: None, path: None, stats, },
+    }
+}
+
+// Portfolio member built on the incremental planner: runs `slice` sized budgets until
+// the planner is done, `limit` iterations are spent or the portfolio is cancelled.
+pub fn run_cancellable<P>(
+    mut planner: IncrementalPlanner<P>,
+    problem: &mut P,
+    slice: &Budget,
+    limit: usize,
+    cancellation: &Cancellation,
+) ->
+    Result<Attempt<P::RttPath>, P::Error>
+    where P: GoalDistance,
+          P::RttNodeRef: Clone,
+{
+    while !cancellation.is_cancelled() && planner.progress().iterations < limit {
+        if planner.run(problem, slice)? == RunStatus::Done {
+            break;
+        }
+    }
+    let iterations = planner.progress().iterations;
+    let path = planner.into_state().into_path();
+    Ok(Attempt { path, iterations, })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+    use std::time::Duration;
+    use super::{race, run_cancellable, Attempt, BoxedMember, Cancellation, Selection};
+    use budget::{Budget, IncrementalPlanner};
+    use util::testing::IntegerLine;
+
+    #[test]
+    fn first_cancels_the_rest() {
+        let planners: Vec<BoxedMember<Vec<usize>>> = vec![
+            Box::new(|_index, cancellation| {
+                let mut iterations = 0;
+                while !cancellation.is_cancelled() {
+                    iterations += 1;
+                    thread::sleep(Duration::from_millis(1));
+                }
+                Attempt { path: None, iterations, }
+            }),
+            Box::new(|index, _cancellation| Attempt { path: Some(vec![index; 3]), iterations: 7, }),
+        ];
+        let result = race(planners, Selection::First, |path: &Vec<usize>| path.len() as f64);
+        assert_eq!(result.winner, Some(1));
+        assert_eq!(result.path, Some(vec![1, 1, 1]));
+        assert!(!result.stats[0].solved);
+        assert!(result.stats[1].solved);
+        assert_eq!(result.stats[1].iterations, 7);
+        assert_eq!(result.stats[1].cost, Some(3.0));
+    }
+
+    #[test]
+    fn best_waits_for_everyone() {
+        let planners: Vec<_> = (0 .. 4).collect();
+        let result = race(
+            planners.iter().map(|&length| move |_index: usize, _cancellation: &super::Cancellation| {
+                thread::sleep(Duration::from_millis(length as u64));
+                Attempt { path: if length == 0 { None } else { Some(vec![0; 5 - length]) }, iterations: length, }
+            }).collect(),
+            Selection::Best,
+            |path: &Vec<usize>| path.len() as f64,
+        );
+        assert_eq!(result.winner, Some(3));
+        assert_eq!(result.path, Some(vec![0, 0]));
+        assert_eq!(result.stats.iter().filter(|member| member.solved).count(), 3);
+    }
+
+    #[test]
+    fn incremental_losers_stop() {
+        // losers reject every sample and only return once they notice the cancellation
+        let goals = vec![None, Some(3), None];
+        let result = race(
+            goals.into_iter().map(|goal| move |_index: usize, cancellation: &Cancellation| {
+                let mut problem = IntegerLine::new(goal);
+                if goal.is_none() {
+                    problem = problem.with_reject(|_| true);
+                }
+                run_cancellable(IncrementalPlanner::new(()), &mut problem, &Budget::iterations(1), usize::MAX, cancellation)
+                    .unwrap()
+            }).collect(),
+            Selection::First,
+            |path: &Vec<i32>| path.len() as f64,
+        );
+        assert_eq!(result.winner, Some(1));
+        assert_eq!(result.path, Some(vec![0, 1, 2, 3]));
+        assert_eq!(result.stats[1].iterations, 3);
+        assert!(!result.stats[0].solved && !result.stats[2].solved);
+    }
+}
@@ -0,0 +1,266 @@
+use super::{
+    RecoverableResult,
+    PlannerInit,
+    Planner,
+    PlannerRttNode,
+    PlannerGoalCheck,
+    PlannerGoalReached,
+    PlannerReadyToSample,
+    PlannerSample,
+    PlannerClosestNodeFound,
+};
+use error::{Stage, PlannerError};
+
+// Problem
+
+// All the transitions of the protocol in one place, plus the transition decision the
+// typestate chain otherwise leaves to the caller's control flow.
+pub trait Problem {
+    type EmptyRtt;
+    type Rtt;
+    type RttNodeRef;
+    type Sample;
+    type RttPath;
+    type Error;
+
+    // the `add_root`, `prepare_sample`, `has_transition` and `no_transition` transitions
+    // hand their consumed arguments back on failure, like `build_path`
+    fn add_root(&mut self, empty_rtt: Self::EmptyRtt) -> RecoverableResult<Self::Rtt, Self::EmptyRtt, Self::Error>;
+
+    fn root_node(&mut self, rtt: &mut Self::Rtt) -> Result<Self::RttNodeRef, Self::Error>;
+
+    fn goal_check(&mut self, rtt: &Self::Rtt, node_ref: &Self::RttNodeRef) -> Result<bool, Self::Error>;
+
+    fn build_path(&mut self, rtt: Self::Rtt, node_ref: Self::RttNodeRef) -> BuildPathResult<Self>;
+
+    fn prepare_sample(&mut self, rtt: &mut Self::Rtt, node_ref: Self::RttNodeRef) ->
+        RecoverableResult<(), Self::RttNodeRef, Self::Error>;
+
+    fn sample(&mut self, rtt: &mut Self::Rtt) -> Result<Self::Sample, Self::Error>;
+
+    fn closest_to_sample(&mut self, rtt: &mut Self::Rtt, sample: &Self::Sample) -> Result<Self::RttNodeRef, Self::Error>;
+
+    fn transition_exists(&mut self, rtt: &Self::Rtt, node_ref: &Self::RttNodeRef, sample: &Self::Sample) -> bool;
+
+    fn has_transition(
+        &mut self,
+        rtt: &mut Self::Rtt,
+        node_ref: Self::RttNodeRef,
+        sample: Self::Sample,
+    ) ->
+        HasTransitionResult<Self>;
+
+    fn no_transition(&mut self, rtt: &mut Self::Rtt, node_ref: Self::RttNodeRef) ->
+        RecoverableResult<(), Self::RttNodeRef, Self::Error>;
+}
+
+// PlannerState
+
+// Any point of the protocol as a single type, so an unfinished planner can be kept in
+// a struct field and advanced one transition at a time.
+pub enum PlannerState<P> where P: Problem {
+    Init(PlannerInit<P::EmptyRtt>),
+    Planner(Planner<P::Rtt>),
+    RttNode(PlannerRttNode<P::Rtt, P::RttNodeRef>),
+    GoalReached(PlannerGoalReached<P::Rtt, P::RttNodeRef>),
+    ReadyToSample(PlannerReadyToSample<P::Rtt>),
+    Sample(PlannerSample<P::Rtt, P::Sample>),
+    ClosestNodeFound(PlannerClosestNodeFound<P::Rtt, P::RttNodeRef, P::Sample>),
+    Done(P::RttPath),
+}
+
+impl<P> Clone for PlannerState<P>
+    where P: Problem,
+          P::EmptyRtt: Clone,
+          P::Rtt: Clone,
+          P::RttNodeRef: Clone,
+          P::Sample: Clone,
+          P::RttPath: Clone,
+{
+    fn clone(&self) -> PlannerState<P> {
+        match *self {
+            PlannerState::Init(ref planner) =>
+                PlannerState::Init(planner.clone()),
+            PlannerState::Planner(ref planner) =>
+                PlannerState::Planner(planner.clone()),
+            PlannerState::RttNode(ref planner) =>
+                PlannerState::RttNode(planner.clone()),
+            PlannerState::GoalReached(ref planner) =>
+                PlannerState::GoalReached(planner.clone()),
+            PlannerState::ReadyToSample(ref planner) =>
+                PlannerState::ReadyToSample(planner.clone()),
+            PlannerState::Sample(ref planner) =>
+                PlannerState::Sample(planner.clone()),
+            PlannerState::ClosestNodeFound(ref planner) =>
+                PlannerState::ClosestNodeFound(planner.clone()),
+            PlannerState::Done(ref path) =>
+                PlannerState::Done(path.clone()),
+        }
+    }
+}
+
+impl<P> PlannerState<P> where P: Problem {
+    pub fn new(empty_rtt: P::EmptyRtt) -> PlannerState<P> {
+        PlannerState::Init(PlannerInit::new(empty_rtt))
+    }
+
+    pub fn rtt(&self) -> Option<&P::Rtt> {
+        match *self {
+            PlannerState::Init(..) | PlannerState::Planner(..) | PlannerState::Done(..) =>
+                None,
+            PlannerState::RttNode(ref planner) =>
+                Some(planner.rtt()),
+            PlannerState::GoalReached(ref planner) =>
+                Some(planner.rtt()),
+            PlannerState::ReadyToSample(ref planner) =>
+                Some(planner.rtt()),
+            PlannerState::Sample(ref planner) =>
+                Some(planner.rtt()),
+            PlannerState::ClosestNodeFound(ref planner) =>
+                Some(planner.rtt()),
+        }
+    }
+
+    pub fn is_done(&self) -> bool {
+        matches!(*self, PlannerState::Done(..))
+    }
+
+    pub fn path(&self) -> Option<&P::RttPath> {
+        if let PlannerState::Done(ref path) = *self { Some(path) } else { None }
+    }
+
+    pub fn into_path(self) -> Option<P::RttPath> {
+        if let PlannerState::Done(path) = self { Some(path) } else { None }
+    }
+}
+
+// Transitions go through the `_recoverable` variants, so a failed step hands the state back
+// with the tree intact.
+impl<P> PlannerState<P> where P: Problem {
+    // performs one protocol transition, except for a node which is not at the goal: it is goal
+    // checked and prepared for the next sample in the same step. `Done` is left as is.
+    pub fn step(self, problem: &mut P) -> RecoverableResult<PlannerState<P>, PlannerState<P>, P::Error> {
+        self.step_staged(problem)
+            .map_err(|(state, _stage, error)| (state, error))
+    }
+
+    pub fn step_tagged(self, problem: &mut P, iteration: usize) ->
+        RecoverableResult<PlannerState<P>, PlannerState<P>, PlannerError<P::Error>>
+    {
+        self.step_staged(problem)
+            .map_err(|(state, stage, error)| (state, PlannerError::new(stage, iteration, error)))
+    }
+
+    fn step_staged(self, problem: &mut P) -> Result<PlannerState<P>, StagedFailure<P>> {
+        match self {
+            PlannerState::Init(planner) =>
+                planner.add_root_recoverable(|empty_rtt| problem.add_root(empty_rtt))
+                    .map(PlannerState::Planner)
+                    .map_err(|(planner, error)| (PlannerState::Init(planner), Stage::AddRoot, error)),
+            PlannerState::Planner(planner) =>
+                planner.root_node_recoverable(|rtt: &mut _| problem.root_node(rtt))
+                    .map(PlannerState::RttNode)
+                    .map_err(|(planner, error)| (PlannerState::Planner(planner), Stage::RootNode, error)),
+            // a node which is not at the goal is prepared for the next sample right away
+            PlannerState::RttNode(planner) =>
+                match planner.goal_check_recoverable(|rtt: &_, node_ref: &_| problem.goal_check(rtt, node_ref)) {
+                    Ok(PlannerGoalCheck::Reached(planner)) =>
+                        Ok(PlannerState::GoalReached(planner)),
+                    Ok(PlannerGoalCheck::NotReached(planner)) =>
+                        planner.prepare_sample_recoverable(|rtt: &mut _, node_ref| problem.prepare_sample(rtt, node_ref))
+                            .map(PlannerState::ReadyToSample)
+                            .map_err(|(planner, error)| (PlannerState::RttNode(planner), Stage::PrepareSample, error)),
+                    Err((planner, error)) =>
+                        Err((PlannerState::RttNode(planner), Stage::GoalCheck, error)),
+                },
+            PlannerState::GoalReached(planner) =>
+                planner.into_path_recoverable(|rtt, node_ref| problem.build_path(rtt, node_ref))
+                    .map(PlannerState::Done)
+                    .map_err(|(planner, error)| (PlannerState::GoalReached(planner), Stage::IntoPath, error)),
+            PlannerState::ReadyToSample(planner) =>
+                planner.sample_recoverable(|rtt: &mut _| problem.sample(rtt))
+                    .map(PlannerState::Sample)
+                    .map_err(|(planner, error)| (PlannerState::ReadyToSample(planner), Stage::Sample, error)),
+            PlannerState::Sample(planner) =>
+                planner.closest_to_sample_recoverable(|rtt: &mut _, sample: &_| problem.closest_to_sample(rtt, sample))
+                    .map(PlannerState::ClosestNodeFound)
+                    .map_err(|(planner, error)| (PlannerState::Sample(planner), Stage::ClosestToSample, error)),
+            PlannerState::ClosestNodeFound(planner) =>
+                if problem.transition_exists(planner.rtt(), planner.node_ref(), planner.sample()) {
+                    planner.has_transition_recoverable(|rtt: &mut _, node_ref, sample| problem.has_transition(rtt, node_ref, sample))
+                        .map(PlannerState::RttNode)
+                        .map_err(|(planner, error)| (PlannerState::ClosestNodeFound(planner), Stage::HasTransition, error))
+                } else {
+                    planner.no_transition_recoverable(|rtt: &mut _, node_ref| problem.no_transition(rtt, node_ref))
+                        .map(PlannerState::ReadyToSample)
+                        .map_err(|(planner, error)| (PlannerState::ClosestNodeFound(planner), Stage::NoTransition, error))
+                },
+            done @ PlannerState::Done(..) =>
+                Ok(done),
+        }
+    }
+}
+
+// path or the rtt and the node handed back by a failed `Problem::build_path`
+pub type BuildPathResult<P> =
+    RecoverableResult<<P as Problem>::RttPath, (<P as Problem>::Rtt, <P as Problem>::RttNodeRef), <P as Problem>::Error>;
+
+// new node or the node and the sample handed back by a failed `Problem::has_transition`
+pub type HasTransitionResult<P> =
+    RecoverableResult<<P as Problem>::RttNodeRef, (<P as Problem>::RttNodeRef, <P as Problem>::Sample), <P as Problem>::Error>;
+
+// state handed back by a failed step along with the stage which failed
+type StagedFailure<P> = (PlannerState<P>, Stage, <P as Problem>::Error);
+
+#[cfg(test)]
+mod tests {
+    use super::PlannerState;
+    use error::Stage;
+    use util::testing::IntegerLine;
+
+    fn step(state: PlannerState<IntegerLine>, problem: &mut IntegerLine) -> PlannerState<IntegerLine> {
+        state.step(problem).unwrap_or_else(|(_, error)| panic!("{}", error))
+    }
+
+    struct Agent {
+        planner: Option<PlannerState<IntegerLine>>,
+        frames: usize,
+    }
+
+    impl Agent {
+        fn frame(&mut self, problem: &mut IntegerLine) {
+            self.frames += 1;
+            let planner = self.planner.take().unwrap();
+            self.planner = Some(step(planner, problem));
+        }
+    }
+
+    #[test]
+    fn suspend_and_resume() {
+        let mut problem = IntegerLine::new(Some(2)).with_reject(|sample| sample == 2);
+        let mut agent = Agent { planner: Some(PlannerState::new(())), frames: 0, };
+        while !agent.planner.as_ref().unwrap().is_done() {
+            agent.frame(&mut problem);
+        }
+        // add_root and root_node, one step of goal check and prepare_sample for each of the root
+        // and the first extension, sample/closest/decision per sample, the goal check which
+        // succeeds and into_path
+        assert_eq!(agent.frames, 2 + 2 + 3 * 3 + 1 + 1);
+        assert_eq!(agent.planner.unwrap().into_path(), Some(vec![0, 1, 2]));
+    }
+
+    #[test]
+    fn failed_goal_check() {
+        let mut problem = IntegerLine::new(Some(0)).with_goal_check_failures(1);
+        let state = step(step(PlannerState::new(()), &mut problem), &mut problem);
+        let (state, error) = match state.step_tagged(&mut problem, 4) {
+            Err(failure) => failure,
+            Ok(..) => panic!("goal check should fail"),
+        };
+        assert_eq!((error.stage(), error.iteration(), *error.error()), (Stage::GoalCheck, 4, "goal check failure"));
+        assert!(matches!(state, PlannerState::RttNode(..)));
+        let state = step(state, &mut problem);
+        assert!(matches!(state, PlannerState::GoalReached(..)));
+        assert_eq!(step(state, &mut problem).into_path(), Some(vec![0]));
+    }
+}
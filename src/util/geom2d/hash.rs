@@ -0,0 +1,61 @@
+use std::collections::HashMap;
+
+use super::{Point, Aabb};
+use util::grid::traversal::SegmentCells;
+
+// objects overlapping more buckets than this are kept aside and checked by every query
+const MAX_BUCKETS_PER_OBJECT: f64 = 1024.0;
+
+// Uniform spatial hash storing object indices by the buckets their bounding boxes overlap.
+pub struct SpatialHash {
+    cell_size: f64,
+    buckets: HashMap<(isize, isize), Vec<usize>>,
+    large: Vec<usize>,
+}
+
+impl SpatialHash {
+    pub fn new(cell_size: f64) -> SpatialHash {
+        assert!(cell_size > 0.0, "spatial hash cell size should be positive");
+        SpatialHash { cell_size, buckets: HashMap::new(), large: Vec::new(), }
+    }
+
+    fn bucket(&self, point: &Point) -> (isize, isize) {
+        ((point[0] / self.cell_size).floor() as isize, (point[1] / self.cell_size).floor() as isize)
+    }
+
+    pub fn insert(&mut self, index: usize, bounds: &Aabb) {
+        assert!(bounds.min.iter().chain(bounds.max.iter()).all(|v| v.is_finite()), "spatial hash object bounds should be finite");
+        let (min_x, min_y) = self.bucket(&bounds.min);
+        let (max_x, max_y) = self.bucket(&bounds.max);
+        let buckets_count = (max_x as f64 - min_x as f64 + 1.0) * (max_y as f64 - min_y as f64 + 1.0);
+        if buckets_count > MAX_BUCKETS_PER_OBJECT {
+            self.large.push(index);
+            return;
+        }
+        for x in min_x ..= max_x {
+            for y in min_y ..= max_y {
+                self.buckets.entry((x, y)).or_default().push(index);
+            }
+        }
+    }
+
+    pub fn query_point(&self, point: &Point) -> impl Iterator<Item = usize> + '_ {
+        self.buckets.get(&self.bucket(point))
+            .into_iter()
+            .flat_map(|indices| indices.iter().cloned())
+            .chain(self.large.iter().cloned())
+    }
+
+    // candidates are sorted and unique
+    pub fn query_segment(&self, from: &Point, to: &Point) -> Vec<usize> {
+        let scale = |point: &Point| [point[0] / self.cell_size, point[1] / self.cell_size];
+        let mut candidates: Vec<_> = SegmentCells::new(&scale(from), &scale(to))
+            .filter_map(|bucket| self.buckets.get(&bucket))
+            .flat_map(|indices| indices.iter().cloned())
+            .chain(self.large.iter().cloned())
+            .collect();
+        candidates.sort_unstable();
+        candidates.dedup();
+        candidates
+    }
+}
@@ -0,0 +1,363 @@
+use util::validity::{StateValidityChecker, MotionValidator};
+
+pub mod hash;
+
+use self::hash::SpatialHash;
+
+pub type Point = [f64; 2];
+
+fn sub(a: &Point, b: &Point) -> Point {
+    [a[0] - b[0], a[1] - b[1]]
+}
+
+fn dot(a: &Point, b: &Point) -> f64 {
+    a[0] * b[0] + a[1] * b[1]
+}
+
+fn cross(a: &Point, b: &Point) -> f64 {
+    a[0] * b[1] - a[1] * b[0]
+}
+
+pub fn point_segment_distance(point: &Point, from: &Point, to: &Point) -> f64 {
+    let seg = sub(to, from);
+    let len_sq = dot(&seg, &seg);
+    let t = if len_sq > 0.0 { (dot(&sub(point, from), &seg) / len_sq).clamp(0.0, 1.0) } else { 0.0 };
+    let closest = [from[0] + seg[0] * t, from[1] + seg[1] * t];
+    let diff = sub(point, &closest);
+    dot(&diff, &diff).sqrt()
+}
+
+pub fn segments_intersect(a_from: &Point, a_to: &Point, b_from: &Point, b_to: &Point) -> bool {
+    fn orientation(a: &Point, b: &Point, c: &Point) -> f64 {
+        cross(&sub(b, a), &sub(c, a))
+    }
+
+    fn on_segment(from: &Point, to: &Point, point: &Point) -> bool {
+        point[0] >= from[0].min(to[0]) && point[0] <= from[0].max(to[0]) &&
+            point[1] >= from[1].min(to[1]) && point[1] <= from[1].max(to[1])
+    }
+
+    let d1 = orientation(b_from, b_to, a_from);
+    let d2 = orientation(b_from, b_to, a_to);
+    let d3 = orientation(a_from, a_to, b_from);
+    let d4 = orientation(a_from, a_to, b_to);
+    if ((d1 > 0.0 && d2 < 0.0) || (d1 < 0.0 && d2 > 0.0)) && ((d3 > 0.0 && d4 < 0.0) || (d3 < 0.0 && d4 > 0.0)) {
+        return true;
+    }
+    (d1 == 0.0 && on_segment(b_from, b_to, a_from)) ||
+        (d2 == 0.0 && on_segment(b_from, b_to, a_to)) ||
+        (d3 == 0.0 && on_segment(a_from, a_to, b_from)) ||
+        (d4 == 0.0 && on_segment(a_from, a_to, b_to))
+}
+
+// Aabb
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Aabb {
+    pub min: Point,
+    pub max: Point,
+}
+
+impl Aabb {
+    pub fn new(min: Point, max: Point) -> Aabb {
+        assert!(min[0] <= max[0] && min[1] <= max[1], "invalid aabb corners");
+        Aabb { min, max, }
+    }
+
+    pub fn contains_point(&self, point: &Point) -> bool {
+        point[0] >= self.min[0] && point[0] <= self.max[0] &&
+            point[1] >= self.min[1] && point[1] <= self.max[1]
+    }
+
+    pub fn intersects(&self, other: &Aabb) -> bool {
+        self.min[0] <= other.max[0] && other.min[0] <= self.max[0] &&
+            self.min[1] <= other.max[1] && other.min[1] <= self.max[1]
+    }
+
+    pub fn intersects_segment(&self, from: &Point, to: &Point) -> bool {
+        // slab test
+        let mut t_enter: f64 = 0.0;
+        let mut t_exit: f64 = 1.0;
+        for axis in 0 .. 2 {
+            let delta = to[axis] - from[axis];
+            if delta == 0.0 {
+                if from[axis] < self.min[axis] || from[axis] > self.max[axis] {
+                    return false;
+                }
+            } else {
+                let t_a = (self.min[axis] - from[axis]) / delta;
+                let t_b = (self.max[axis] - from[axis]) / delta;
+                t_enter = t_enter.max(t_a.min(t_b));
+                t_exit = t_exit.min(t_a.max(t_b));
+                if t_enter > t_exit {
+                    return false;
+                }
+            }
+        }
+        true
+    }
+}
+
+// Circle
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Circle {
+    pub center: Point,
+    pub radius: f64,
+}
+
+impl Circle {
+    pub fn new(center: Point, radius: f64) -> Circle {
+        assert!(radius >= 0.0, "circle radius should not be negative");
+        Circle { center, radius, }
+    }
+
+    pub fn bounds(&self) -> Aabb {
+        Aabb {
+            min: [self.center[0] - self.radius, self.center[1] - self.radius],
+            max: [self.center[0] + self.radius, self.center[1] + self.radius],
+        }
+    }
+
+    pub fn contains_point(&self, point: &Point) -> bool {
+        let diff = sub(point, &self.center);
+        dot(&diff, &diff) <= self.radius * self.radius
+    }
+
+    pub fn intersects_segment(&self, from: &Point, to: &Point) -> bool {
+        point_segment_distance(&self.center, from, to) <= self.radius
+    }
+}
+
+// Polygon
+
+// Simple polygon with vertices in any orientation, the closing edge is implicit.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Polygon {
+    vertices: Vec<Point>,
+    bounds: Aabb,
+}
+
+impl Polygon {
+    pub fn new(vertices: Vec<Point>) -> Polygon {
+        assert!(vertices.len() >= 3, "polygon should have at least 3 vertices");
+        let mut bounds = Aabb { min: vertices[0], max: vertices[0], };
+        for vertex in vertices.iter() {
+            for (axis, &value) in vertex.iter().enumerate() {
+                bounds.min[axis] = bounds.min[axis].min(value);
+                bounds.max[axis] = bounds.max[axis].max(value);
+            }
+        }
+        Polygon { vertices, bounds, }
+    }
+
+    pub fn vertices(&self) -> &[Point] {
+        &self.vertices
+    }
+
+    pub fn bounds(&self) -> Aabb {
+        self.bounds
+    }
+
+    pub fn edges(&self) -> impl Iterator<Item = (&Point, &Point)> {
+        self.vertices.iter().zip(self.vertices.iter().cycle().skip(1))
+    }
+
+    pub fn contains_point(&self, point: &Point) -> bool {
+        if !self.bounds.contains_point(point) {
+            return false;
+        }
+        // boundary counts as inside
+        if self.edges().any(|(a, b)| point_segment_distance(point, a, b) == 0.0) {
+            return true;
+        }
+        // even-odd ray casting
+        let mut inside = false;
+        for (a, b) in self.edges() {
+            if (a[1] > point[1]) != (b[1] > point[1]) {
+                let x = a[0] + (point[1] - a[1]) * (b[0] - a[0]) / (b[1] - a[1]);
+                if point[0] < x {
+                    inside = !inside;
+                }
+            }
+        }
+        inside
+    }
+
+    pub fn intersects_segment(&self, from: &Point, to: &Point) -> bool {
+        self.bounds.intersects_segment(from, to) && (
+            self.contains_point(from) ||
+                self.edges().any(|(a, b)| segments_intersect(from, to, a, b))
+        )
+    }
+}
+
+// Shape
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum Shape {
+    Box(Aabb),
+    Circle(Circle),
+    Polygon(Polygon),
+}
+
+impl Shape {
+    pub fn bounds(&self) -> Aabb {
+        match *self {
+            Shape::Box(ref aabb) => *aabb,
+            Shape::Circle(ref circle) => circle.bounds(),
+            Shape::Polygon(ref polygon) => polygon.bounds(),
+        }
+    }
+
+    pub fn contains_point(&self, point: &Point) -> bool {
+        match *self {
+            Shape::Box(ref aabb) => aabb.contains_point(point),
+            Shape::Circle(ref circle) => circle.contains_point(point),
+            Shape::Polygon(ref polygon) => polygon.contains_point(point),
+        }
+    }
+
+    pub fn intersects_segment(&self, from: &Point, to: &Point) -> bool {
+        match *self {
+            Shape::Box(ref aabb) => aabb.intersects_segment(from, to),
+            Shape::Circle(ref circle) => circle.intersects_segment(from, to),
+            Shape::Polygon(ref polygon) => polygon.intersects_segment(from, to),
+        }
+    }
+}
+
+// ObstacleWorld
+
+pub struct ObstacleWorld {
+    bounds: Aabb,
+    obstacles: Vec<Shape>,
+    broadphase: SpatialHash,
+}
+
+impl ObstacleWorld {
+    // `cell_size` is the spatial hash bucket side, roughly the typical obstacle size works well
+    pub fn new(bounds: Aabb, cell_size: f64) -> ObstacleWorld {
+        ObstacleWorld {
+            bounds,
+            obstacles: Vec::new(),
+            broadphase: SpatialHash::new(cell_size),
+        }
+    }
+
+    pub fn bounds(&self) -> &Aabb {
+        &self.bounds
+    }
+
+    pub fn add_obstacle(&mut self, shape: Shape) -> usize {
+        let index = self.obstacles.len();
+        self.broadphase.insert(index, &shape.bounds());
+        self.obstacles.push(shape);
+        index
+    }
+
+    pub fn obstacles(&self) -> &[Shape] {
+        &self.obstacles
+    }
+
+    pub fn is_point_free(&self, point: &Point) -> bool {
+        self.bounds.contains_point(point) &&
+            self.broadphase.query_point(point)
+            .all(|index| !self.obstacles[index].contains_point(point))
+    }
+
+    pub fn is_segment_free(&self, from: &Point, to: &Point) -> bool {
+        self.bounds.contains_point(from) && self.bounds.contains_point(to) &&
+            self.broadphase.query_segment(from, to)
+            .into_iter()
+            .all(|index| !self.obstacles[index].intersects_segment(from, to))
+    }
+}
+
+impl StateValidityChecker<Point> for ObstacleWorld {
+    fn is_valid(&self, state: &Point) -> bool {
+        self.is_point_free(state)
+    }
+}
+
+impl StateValidityChecker<Vec<f64>> for ObstacleWorld {
+    fn is_valid(&self, state: &Vec<f64>) -> bool {
+        self.is_point_free(&[state[0], state[1]])
+    }
+}
+
+impl MotionValidator<Point> for ObstacleWorld {
+    fn is_motion_valid(&self, from: &Point, to: &Point) -> bool {
+        self.is_segment_free(from, to)
+    }
+}
+
+impl MotionValidator<Vec<f64>> for ObstacleWorld {
+    fn is_motion_valid(&self, from: &Vec<f64>, to: &Vec<f64>) -> bool {
+        self.is_segment_free(&[from[0], from[1]], &[to[0], to[1]])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shapes() {
+        let triangle = Polygon::new(vec![[0.0, 0.0], [4.0, 0.0], [0.0, 4.0]]);
+        assert!(triangle.contains_point(&[1.0, 1.0]));
+        assert!(triangle.contains_point(&[2.0, 2.0]));
+        assert!(!triangle.contains_point(&[2.1, 2.1]));
+        assert!(triangle.intersects_segment(&[-1.0, 1.0], &[1.0, 1.0]));
+        assert!(triangle.intersects_segment(&[0.5, 0.5], &[0.6, 0.6]));
+        assert!(!triangle.intersects_segment(&[3.0, 3.0], &[5.0, 1.0]));
+
+        let circle = Circle::new([0.0, 0.0], 1.0);
+        assert!(circle.intersects_segment(&[-2.0, 0.5], &[2.0, 0.5]));
+        assert!(!circle.intersects_segment(&[-2.0, 1.5], &[2.0, 1.5]));
+
+        let aabb = Aabb::new([0.0, 0.0], [1.0, 1.0]);
+        assert!(aabb.intersects_segment(&[-1.0, -1.0], &[2.0, 2.0]));
+        assert!(aabb.intersects_segment(&[0.5, 2.0], &[0.5, 0.5]));
+        assert!(!aabb.intersects_segment(&[1.5, -1.0], &[3.0, 2.0]));
+    }
+
+    #[test]
+    fn world_queries() {
+        let mut world = ObstacleWorld::new(Aabb::new([0.0, 0.0], [10.0, 10.0]), 1.5);
+        world.add_obstacle(Shape::Box(Aabb::new([4.0, 0.0], [5.0, 8.0])));
+        world.add_obstacle(Shape::Circle(Circle::new([8.0, 8.0], 1.0)));
+        world.add_obstacle(Shape::Polygon(Polygon::new(vec![[1.0, 8.0], [3.0, 8.0], [2.0, 9.5]])));
+
+        assert!(world.is_valid(&vec![1.0, 1.0]));
+        assert!(!world.is_valid(&vec![4.5, 4.0]));
+        assert!(!world.is_valid(&vec![11.0, 1.0]));
+        assert!(!world.is_valid(&vec![2.0, 8.5]));
+
+        assert!(!world.is_motion_valid(&vec![1.0, 1.0], &vec![9.0, 1.0]));
+        assert!(world.is_motion_valid(&vec![0.5, 9.9], &vec![9.5, 9.9]));
+        assert!(!world.is_motion_valid(&vec![1.0, 9.0], &vec![9.0, 9.8]));
+        assert!(world.is_motion_valid(&vec![3.5, 1.0], &vec![3.5, 7.5]));
+        assert!(!world.is_motion_valid(&vec![0.5, 8.5], &vec![3.5, 8.5]));
+        assert!(world.is_motion_valid(&vec![3.0, 9.0], &vec![6.0, 9.0]));
+    }
+
+    #[test]
+    fn large_obstacle() {
+        let mut world = ObstacleWorld::new(Aabb::new([0.0, 0.0], [1000.0, 1000.0]), 0.1);
+        world.add_obstacle(Shape::Circle(Circle::new([500.0, 500.0], 400.0)));
+        world.add_obstacle(Shape::Box(Aabb::new([10.0, 10.0], [10.5, 10.5])));
+        assert!(!world.is_valid(&vec![500.0, 500.0]));
+        assert!(!world.is_valid(&vec![10.2, 10.2]));
+        assert!(world.is_valid(&vec![50.0, 950.0]));
+        assert!(!world.is_motion_valid(&vec![50.0, 950.0], &vec![950.0, 50.0]));
+        assert!(world.is_motion_valid(&vec![50.0, 950.0], &vec![50.0, 900.0]));
+    }
+
+    #[test]
+    #[should_panic(expected = "spatial hash object bounds should be finite")]
+    fn infinite_obstacle() {
+        let mut world = ObstacleWorld::new(Aabb::new([0.0, 0.0], [10.0, 10.0]), 1.0);
+        world.add_obstacle(Shape::Circle(Circle::new([5.0, 5.0], f64::INFINITY)));
+    }
+}
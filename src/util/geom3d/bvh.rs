@@ -0,0 +1,99 @@
+use super::{Aabb, Point};
+
+const LEAF_SIZE: usize = 4;
+
+enum NodeKind {
+    Leaf { start: usize, count: usize, },
+    Inner { left: usize, right: usize, },
+}
+
+struct Node {
+    bounds: Aabb,
+    kind: NodeKind,
+}
+
+// Static bounding volume hierarchy over object bounding boxes built with median splits
+// along the longest axis of the objects centers.
+pub struct Bvh {
+    nodes: Vec<Node>,
+    indices: Vec<usize>,
+}
+
+impl Bvh {
+    pub fn build(bounds: Vec<Aabb>) -> Bvh {
+        let mut bvh = Bvh {
+            nodes: Vec::new(),
+            indices: (0 .. bounds.len()).collect(),
+        };
+        if !bounds.is_empty() {
+            bvh.build_node(&bounds, 0, bounds.len());
+        }
+        bvh
+    }
+
+    fn build_node(&mut self, bounds: &[Aabb], start: usize, end: usize) -> usize {
+        let node_bounds = self.indices[start + 1 .. end].iter()
+            .fold(bounds[self.indices[start]], |acc, &index| acc.union(&bounds[index]));
+        let node_index = self.nodes.len();
+        self.nodes.push(Node { bounds: node_bounds, kind: NodeKind::Leaf { start, count: end - start, }, });
+        if end - start <= LEAF_SIZE {
+            return node_index;
+        }
+
+        let centers = self.indices[start .. end].iter()
+            .map(|&index| bounds[index].center())
+            .fold(None, |acc: Option<Aabb>, center| {
+                let point_box = Aabb { min: center, max: center, };
+                Some(acc.map(|acc| acc.union(&point_box)).unwrap_or(point_box))
+            })
+            .unwrap();
+        let axis = (0 .. 3)
+            .max_by(|&a, &b| {
+                let extent_a = centers.max[a] - centers.min[a];
+                let extent_b = centers.max[b] - centers.min[b];
+                extent_a.total_cmp(&extent_b)
+            })
+            .unwrap();
+        let mid = (start + end) / 2;
+        self.indices[start .. end].select_nth_unstable_by(mid - start, |&a, &b| {
+            bounds[a].center()[axis].total_cmp(&bounds[b].center()[axis])
+        });
+
+        let left = self.build_node(bounds, start, mid);
+        let right = self.build_node(bounds, mid, end);
+        self.nodes[node_index].kind = NodeKind::Inner { left, right, };
+        node_index
+    }
+
+    // visits objects whose bounds are hit by the segment inflated by `radius`
+    // and returns `true` as soon as `pred` holds for any of them
+    pub fn any_swept_sphere<F>(&self, from: &Point, to: &Point, radius: f64, mut pred: F) -> bool
+        where F: FnMut(usize) -> bool
+    {
+        if self.nodes.is_empty() {
+            return false;
+        }
+        let mut stack = vec![0];
+        while let Some(node_index) = stack.pop() {
+            let node = &self.nodes[node_index];
+            if !node.bounds.inflate(radius).intersects_segment(from, to) {
+                continue;
+            }
+            match node.kind {
+                NodeKind::Leaf { start, count, } =>
+                    if self.indices[start .. start + count].iter().any(|&index| pred(index)) {
+                        return true;
+                    },
+                NodeKind::Inner { left, right, } => {
+                    stack.push(left);
+                    stack.push(right);
+                },
+            }
+        }
+        false
+    }
+
+    pub fn any_point<F>(&self, point: &Point, pred: F) -> bool where F: FnMut(usize) -> bool {
+        self.any_swept_sphere(point, point, 0.0, pred)
+    }
+}
@@ -0,0 +1,300 @@
+use util::validity::{StateValidityChecker, MotionValidator};
+
+pub mod bvh;
+
+use self::bvh::Bvh;
+
+pub type Point = [f64; 3];
+
+fn sub(a: &Point, b: &Point) -> Point {
+    [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
+
+fn dot(a: &Point, b: &Point) -> f64 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+fn lerp(from: &Point, to: &Point, t: f64) -> Point {
+    [from[0] + (to[0] - from[0]) * t, from[1] + (to[1] - from[1]) * t, from[2] + (to[2] - from[2]) * t]
+}
+
+pub fn point_segment_distance(point: &Point, from: &Point, to: &Point) -> f64 {
+    let seg = sub(to, from);
+    let len_sq = dot(&seg, &seg);
+    let t = if len_sq > 0.0 { (dot(&sub(point, from), &seg) / len_sq).clamp(0.0, 1.0) } else { 0.0 };
+    let diff = sub(point, &lerp(from, to, t));
+    dot(&diff, &diff).sqrt()
+}
+
+// Aabb
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Aabb {
+    pub min: Point,
+    pub max: Point,
+}
+
+impl Aabb {
+    pub fn new(min: Point, max: Point) -> Aabb {
+        assert!((0 .. 3).all(|axis| min[axis] <= max[axis]), "invalid aabb corners");
+        Aabb { min, max, }
+    }
+
+    pub fn union(&self, other: &Aabb) -> Aabb {
+        let mut result = *self;
+        for axis in 0 .. 3 {
+            result.min[axis] = result.min[axis].min(other.min[axis]);
+            result.max[axis] = result.max[axis].max(other.max[axis]);
+        }
+        result
+    }
+
+    pub fn inflate(&self, margin: f64) -> Aabb {
+        Aabb {
+            min: [self.min[0] - margin, self.min[1] - margin, self.min[2] - margin],
+            max: [self.max[0] + margin, self.max[1] + margin, self.max[2] + margin],
+        }
+    }
+
+    pub fn center(&self) -> Point {
+        lerp(&self.min, &self.max, 0.5)
+    }
+
+    pub fn contains_point(&self, point: &Point) -> bool {
+        (0 .. 3).all(|axis| point[axis] >= self.min[axis] && point[axis] <= self.max[axis])
+    }
+
+    pub fn distance_to_point(&self, point: &Point) -> f64 {
+        (0 .. 3)
+            .map(|axis| {
+                let excess = (self.min[axis] - point[axis]).max(point[axis] - self.max[axis]).max(0.0);
+                excess * excess
+            })
+            .sum::<f64>()
+            .sqrt()
+    }
+
+    pub fn intersects_segment(&self, from: &Point, to: &Point) -> bool {
+        // slab test
+        let mut t_enter: f64 = 0.0;
+        let mut t_exit: f64 = 1.0;
+        for axis in 0 .. 3 {
+            let delta = to[axis] - from[axis];
+            if delta == 0.0 {
+                if from[axis] < self.min[axis] || from[axis] > self.max[axis] {
+                    return false;
+                }
+            } else {
+                let t_a = (self.min[axis] - from[axis]) / delta;
+                let t_b = (self.max[axis] - from[axis]) / delta;
+                t_enter = t_enter.max(t_a.min(t_b));
+                t_exit = t_exit.min(t_a.max(t_b));
+                if t_enter > t_exit {
+                    return false;
+                }
+            }
+        }
+        true
+    }
+
+    pub fn intersects_swept_sphere(&self, from: &Point, to: &Point, radius: f64) -> bool {
+        if !self.inflate(radius).intersects_segment(from, to) {
+            return false;
+        }
+        // distance from a point moving along the segment to a convex box is convex in `t`
+        let (mut lo, mut hi) = (0.0, 1.0);
+        let distance = |t| self.distance_to_point(&lerp(from, to, t));
+        for _ in 0 .. 64 {
+            let mid_a = lo + (hi - lo) / 3.0;
+            let mid_b = hi - (hi - lo) / 3.0;
+            if distance(mid_a) <= radius || distance(mid_b) <= radius {
+                return true;
+            }
+            if distance(mid_a) < distance(mid_b) {
+                hi = mid_b;
+            } else {
+                lo = mid_a;
+            }
+        }
+        distance(lo) <= radius || distance(0.0) <= radius || distance(1.0) <= radius
+    }
+}
+
+// Sphere
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Sphere {
+    pub center: Point,
+    pub radius: f64,
+}
+
+impl Sphere {
+    pub fn new(center: Point, radius: f64) -> Sphere {
+        assert!(radius >= 0.0, "sphere radius should not be negative");
+        Sphere { center, radius, }
+    }
+
+    pub fn bounds(&self) -> Aabb {
+        Aabb { min: self.center, max: self.center, }.inflate(self.radius)
+    }
+
+    pub fn intersects_swept_sphere(&self, from: &Point, to: &Point, radius: f64) -> bool {
+        point_segment_distance(&self.center, from, to) <= self.radius + radius
+    }
+}
+
+// Shape
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Shape {
+    Box(Aabb),
+    Sphere(Sphere),
+}
+
+impl Shape {
+    pub fn bounds(&self) -> Aabb {
+        match *self {
+            Shape::Box(ref aabb) => *aabb,
+            Shape::Sphere(ref sphere) => sphere.bounds(),
+        }
+    }
+
+    pub fn contains_point(&self, point: &Point) -> bool {
+        self.intersects_swept_sphere(point, point, 0.0)
+    }
+
+    pub fn intersects_segment(&self, from: &Point, to: &Point) -> bool {
+        self.intersects_swept_sphere(from, to, 0.0)
+    }
+
+    pub fn intersects_swept_sphere(&self, from: &Point, to: &Point, radius: f64) -> bool {
+        match *self {
+            Shape::Box(ref aabb) if radius == 0.0 => aabb.intersects_segment(from, to),
+            Shape::Box(ref aabb) => aabb.intersects_swept_sphere(from, to, radius),
+            Shape::Sphere(ref sphere) => sphere.intersects_swept_sphere(from, to, radius),
+        }
+    }
+}
+
+// ObstacleScene
+
+pub struct ObstacleScene {
+    bounds: Aabb,
+    obstacles: Vec<Shape>,
+    bvh: Bvh,
+    robot_radius: f64,
+}
+
+impl ObstacleScene {
+    pub fn new(bounds: Aabb, obstacles: Vec<Shape>) -> ObstacleScene {
+        let bvh = Bvh::build(obstacles.iter().map(Shape::bounds).collect());
+        ObstacleScene { bounds, obstacles, bvh, robot_radius: 0.0, }
+    }
+
+    // robot is approximated with a sphere for validity checks, a point robot by default
+    pub fn with_robot_radius(mut self, robot_radius: f64) -> ObstacleScene {
+        assert!(robot_radius >= 0.0, "robot radius should not be negative");
+        self.robot_radius = robot_radius;
+        self
+    }
+
+    pub fn bounds(&self) -> &Aabb {
+        &self.bounds
+    }
+
+    pub fn obstacles(&self) -> &[Shape] {
+        &self.obstacles
+    }
+
+    pub fn is_point_free(&self, point: &Point) -> bool {
+        self.is_swept_sphere_free(point, point, 0.0)
+    }
+
+    pub fn is_segment_free(&self, from: &Point, to: &Point) -> bool {
+        self.is_swept_sphere_free(from, to, 0.0)
+    }
+
+    pub fn is_swept_sphere_free(&self, from: &Point, to: &Point, radius: f64) -> bool {
+        self.bounds.contains_point(from) && self.bounds.contains_point(to) &&
+            !self.bvh.any_swept_sphere(from, to, radius, |index| {
+                self.obstacles[index].intersects_swept_sphere(from, to, radius)
+            })
+    }
+}
+
+fn to_point(state: &[f64]) -> Point {
+    [state[0], state[1], state[2]]
+}
+
+impl StateValidityChecker<Vec<f64>> for ObstacleScene {
+    fn is_valid(&self, state: &Vec<f64>) -> bool {
+        let point = to_point(state);
+        self.is_swept_sphere_free(&point, &point, self.robot_radius)
+    }
+}
+
+impl MotionValidator<Vec<f64>> for ObstacleScene {
+    fn is_motion_valid(&self, from: &Vec<f64>, to: &Vec<f64>) -> bool {
+        self.is_swept_sphere_free(&to_point(from), &to_point(to), self.robot_radius)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use util::testing::xorshift;
+
+    #[test]
+    fn bvh_matches_brute_force() {
+        let mut unit = xorshift(0x2545f4914f6cdd1d);
+        let mut next = || unit() * 100.0;
+        let mut obstacles = Vec::new();
+        for index in 0 .. 500 {
+            let center = [next(), next(), next()];
+            let size = next() / 50.0;
+            obstacles.push(if index % 2 == 0 {
+                Shape::Sphere(Sphere::new(center, size))
+            } else {
+                Shape::Box(Aabb::new(center, [center[0] + size, center[1] + size * 2.0, center[2] + size / 2.0]))
+            });
+        }
+        let scene = ObstacleScene::new(Aabb::new([0.0; 3], [200.0; 3]), obstacles.clone());
+        for _ in 0 .. 300 {
+            let from = [next(), next(), next()];
+            let to = [next() / 5.0 + from[0], next() / 5.0 + from[1], from[2]];
+            let radius = next() / 100.0;
+            let brute = obstacles.iter().all(|shape| !shape.intersects_swept_sphere(&from, &to, radius));
+            assert_eq!(scene.is_swept_sphere_free(&from, &to, radius), brute);
+            let brute = obstacles.iter().all(|shape| !shape.contains_point(&from));
+            assert_eq!(scene.is_point_free(&from), brute);
+        }
+    }
+
+    #[test]
+    fn swept_sphere_box() {
+        let aabb = Aabb::new([0.0, 0.0, 0.0], [1.0, 1.0, 1.0]);
+        // passes the edge at `x = y = 1` at distance `1.5 / sqrt(2)`
+        assert!(!aabb.intersects_swept_sphere(&[2.0, 1.5, 0.5], &[1.5, 2.0, 0.5], 1.05));
+        assert!(aabb.intersects_swept_sphere(&[2.0, 1.5, 0.5], &[1.5, 2.0, 0.5], 1.07));
+        assert!(aabb.intersects_swept_sphere(&[-1.0, 0.5, 0.5], &[2.0, 0.5, 0.5], 0.0));
+        let scene = ObstacleScene::new(Aabb::new([-5.0; 3], [5.0; 3]), vec![Shape::Box(aabb)])
+            .with_robot_radius(0.5);
+        assert!(!scene.is_valid(&vec![1.2, 0.5, 0.5]));
+        assert!(scene.is_valid(&vec![1.6, 0.5, 0.5]));
+        assert!(!scene.is_motion_valid(&vec![-2.0, 1.4, 0.5], &vec![3.0, 1.4, 0.5]));
+        assert!(scene.is_motion_valid(&vec![-2.0, 1.6, 0.5], &vec![3.0, 1.6, 0.5]));
+    }
+
+    #[test]
+    fn nan_bounds_do_not_panic() {
+        let obstacles: Vec<_> = (0 .. 8)
+            .map(|index| {
+                let coord = if index == 3 { f64::NAN } else { index as f64 };
+                Shape::Sphere(Sphere::new([coord, 0.0, 0.0], 0.25))
+            })
+            .collect();
+        let scene = ObstacleScene::new(Aabb::new([-10.0; 3], [10.0; 3]), obstacles);
+        assert!(!scene.is_valid(&vec![5.0, 0.0, 0.0]));
+        assert!(scene.is_valid(&vec![5.5, 0.0, 0.0]));
+    }
+}
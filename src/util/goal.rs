@@ -0,0 +1,222 @@
+use std::marker::PhantomData;
+
+use TransGoalCheck;
+use util::NeverError;
+use util::sample::RandomSource;
+use util::space::{Bounds, Metric, euclidean_distance};
+
+pub trait GoalRegion<S> {
+    fn contains(&self, state: &S) -> bool;
+
+    // zero inside the region
+    fn distance(&self, state: &S) -> f64;
+
+    // a state inside the region, for goal biased sampling; `None` if the region can't be sampled
+    fn sample(&self, _rng: &mut dyn RandomSource) -> Option<S> {
+        None
+    }
+
+    fn as_check<NR, RT, F>(&self, node_state: F) -> GoalRegionCheck<'_, Self, F, S>
+        where Self: Sized,
+              F: for<'a> FnOnce(&'a RT, &'a NR) -> &'a S,
+    {
+        GoalRegionCheck { goal: self, node_state, _marker: PhantomData, }
+    }
+}
+
+pub struct GoalRegionCheck<'g, G: 'g, F, S> {
+    goal: &'g G,
+    node_state: F,
+    _marker: PhantomData<fn(&S)>,
+}
+
+impl<'g, RT, NR, G, F, S> TransGoalCheck<RT, NR> for GoalRegionCheck<'g, G, F, S>
+    where G: GoalRegion<S>,
+          F: for<'a> FnOnce(&'a RT, &'a NR) -> &'a S,
+{
+    type Error = NeverError;
+
+    fn goal_check(self, rtt: &RT, node_ref: &NR) -> Result<bool, NeverError> {
+        Ok(self.goal.contains((self.node_state)(rtt, node_ref)))
+    }
+}
+
+// PointGoal
+
+#[derive(Clone, Debug)]
+pub struct PointGoal<S, M> {
+    pub point: S,
+    pub tolerance: f64,
+    pub metric: M,
+}
+
+impl<S, M> PointGoal<S, M> where M: Metric<S> {
+    pub fn new(point: S, tolerance: f64, metric: M) -> PointGoal<S, M> {
+        PointGoal { point, tolerance, metric, }
+    }
+}
+
+impl<S, M> GoalRegion<S> for PointGoal<S, M> where M: Metric<S>, S: Clone {
+    fn contains(&self, state: &S) -> bool {
+        self.metric.distance(state, &self.point) <= self.tolerance
+    }
+
+    fn distance(&self, state: &S) -> f64 {
+        (self.metric.distance(state, &self.point) - self.tolerance).max(0.0)
+    }
+
+    fn sample(&self, _rng: &mut dyn RandomSource) -> Option<S> {
+        Some(self.point.clone())
+    }
+}
+
+// BoxGoal
+
+#[derive(Clone, Debug)]
+pub struct BoxGoal {
+    pub bounds: Bounds,
+}
+
+impl BoxGoal {
+    pub fn new(bounds: Bounds) -> BoxGoal {
+        BoxGoal { bounds, }
+    }
+}
+
+impl GoalRegion<Vec<f64>> for BoxGoal {
+    fn contains(&self, state: &Vec<f64>) -> bool {
+        self.bounds.contains(state)
+    }
+
+    fn distance(&self, state: &Vec<f64>) -> f64 {
+        state.iter()
+            .zip(self.bounds.lower.iter().zip(self.bounds.upper.iter()))
+            .map(|(&value, (&lower, &upper))| (lower - value).max(value - upper).max(0.0))
+            .map(|gap| gap * gap)
+            .sum::<f64>()
+            .sqrt()
+    }
+
+    fn sample(&self, rng: &mut dyn RandomSource) -> Option<Vec<f64>> {
+        let unit: Vec<_> = (0 .. self.bounds.dimension()).map(|_| rng.next_unit()).collect();
+        Some(self.bounds.from_unit(&unit))
+    }
+}
+
+// BallGoal
+
+#[derive(Clone, Debug)]
+pub struct BallGoal {
+    pub center: Vec<f64>,
+    pub radius: f64,
+}
+
+impl BallGoal {
+    pub fn new(center: Vec<f64>, radius: f64) -> BallGoal {
+        BallGoal { center, radius, }
+    }
+}
+
+impl GoalRegion<Vec<f64>> for BallGoal {
+    fn contains(&self, state: &Vec<f64>) -> bool {
+        euclidean_distance(state, &self.center) <= self.radius
+    }
+
+    fn distance(&self, state: &Vec<f64>) -> f64 {
+        (euclidean_distance(state, &self.center) - self.radius).max(0.0)
+    }
+
+    // uniform over the ball volume
+    fn sample(&self, rng: &mut dyn RandomSource) -> Option<Vec<f64>> {
+        let dimension = self.center.len();
+        let direction: Vec<_> = (0 .. dimension).map(|_| rng.next_normal()).collect();
+        let norm = direction.iter().map(|v| v * v).sum::<f64>().sqrt();
+        if norm == 0.0 {
+            return Some(self.center.clone());
+        }
+        let scale = self.radius * rng.next_unit().powf(1.0 / dimension as f64) / norm;
+        Some(self.center.iter().zip(direction.iter()).map(|(c, v)| c + v * scale).collect())
+    }
+}
+
+// PredicateGoal
+
+pub struct PredicateGoal<S, P, D> {
+    predicate: P,
+    distance: D,
+    _marker: PhantomData<fn(&S)>,
+}
+
+impl<S, P, D> PredicateGoal<S, P, D> where P: Fn(&S) -> bool, D: Fn(&S) -> f64 {
+    pub fn new(predicate: P, distance: D) -> PredicateGoal<S, P, D> {
+        PredicateGoal { predicate, distance, _marker: PhantomData, }
+    }
+}
+
+impl<S, P, D> GoalRegion<S> for PredicateGoal<S, P, D> where P: Fn(&S) -> bool, D: Fn(&S) -> f64 {
+    fn contains(&self, state: &S) -> bool {
+        (self.predicate)(state)
+    }
+
+    fn distance(&self, state: &S) -> f64 {
+        if (self.predicate)(state) { 0.0 } else { (self.distance)(state) }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use {PlannerInit, PlannerGoalCheck};
+    use util::space::Euclidean;
+    use util::rtt::vec_slist::{EmptyRandomTree, RandomTree};
+
+    #[test]
+    fn regions() {
+        let point = PointGoal::new(vec![1.0, 1.0], 0.5, Euclidean);
+        assert!(point.contains(&vec![1.3, 1.0]));
+        assert_eq!(point.distance(&vec![3.0, 1.0]), 1.5);
+
+        let area = BoxGoal::new(Bounds::new(vec![0.0, 0.0], vec![1.0, 2.0]));
+        assert!(area.contains(&vec![0.5, 1.5]));
+        assert_eq!(area.distance(&vec![0.5, 1.5]), 0.0);
+        assert_eq!(area.distance(&vec![4.0, 6.0]), 5.0);
+
+        let ball = BallGoal::new(vec![0.0, 0.0, 0.0], 2.0);
+        let mut seed = 0.1_f64;
+        let mut rng = || { seed = (seed * 7.31 + 0.17).fract(); seed };
+        for _ in 0 .. 100 {
+            assert!(ball.contains(&ball.sample(&mut rng).unwrap()));
+            assert!(area.contains(&area.sample(&mut rng).unwrap()));
+        }
+
+        let even = PredicateGoal::new(|state: &i32| state % 2 == 0, |_state: &i32| 1.0);
+        assert!(even.contains(&4));
+        assert_eq!(even.distance(&3), 1.0);
+        assert_eq!(even.sample(&mut rng), None);
+    }
+
+    #[test]
+    fn goal_check_stage() {
+        let goal = PointGoal::new(vec![2.0], 0.1, Euclidean);
+        let planner_node = PlannerInit::new(EmptyRandomTree::new())
+            .add_root_ok(|empty_rtt: EmptyRandomTree<Vec<f64>>| Ok(empty_rtt.add_root(vec![0.0])))
+            .root_node_ok(|rtt: &mut RandomTree<Vec<f64>>| Ok(rtt.root()));
+        let planner_node = match planner_node.goal_check_ok(goal.as_check(RandomTree::get_state)) {
+            PlannerGoalCheck::Reached(..) => panic!("root is not a goal"),
+            PlannerGoalCheck::NotReached(planner_node) => planner_node,
+        };
+        let planner_node = planner_node
+            .prepare_sample_ok(|_rtt: &mut _, _node_ref| Ok(()))
+            .sample_ok(|_rtt: &mut _| Ok(vec![2.05]))
+            .closest_to_sample_ok(|rtt: &mut RandomTree<Vec<f64>>, _sample: &_| Ok(rtt.root()))
+            .has_transition_ok(|rtt: &mut RandomTree<Vec<f64>>, node_ref, sample| Ok(rtt.expand(node_ref, sample)));
+        match planner_node.goal_check_ok(goal.as_check(RandomTree::get_state)) {
+            PlannerGoalCheck::Reached(planner_goal) => {
+                let path = planner_goal.into_path_ok(|rtt: RandomTree<_>, node_ref| Ok(rtt.forward_path(&node_ref)));
+                assert_eq!(path.states(), &[vec![0.0], vec![2.05]]);
+            },
+            PlannerGoalCheck::NotReached(..) =>
+                panic!("goal is not reached"),
+        }
+    }
+}
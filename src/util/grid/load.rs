@@ -0,0 +1,316 @@
+use std::{io, fmt, error, fs, str};
+use std::path::Path;
+
+use super::OccupancyGrid;
+
+#[derive(Debug)]
+pub enum LoadError {
+    Io(io::Error),
+    EmptyMap,
+    MultipleStarts,
+    MultipleGoals,
+    PgmInvalidMagic,
+    PgmInvalidHeader,
+    PgmUnexpectedEof,
+    PgmInvalidPixel,
+}
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            LoadError::Io(ref err) => write!(f, "map read error: {}", err),
+            LoadError::EmptyMap => write!(f, "map is empty"),
+            LoadError::MultipleStarts => write!(f, "more than one start cell in map"),
+            LoadError::MultipleGoals => write!(f, "more than one goal cell in map"),
+            LoadError::PgmInvalidMagic => write!(f, "not a PGM image: expected P2 or P5 magic"),
+            LoadError::PgmInvalidHeader => write!(f, "invalid PGM header"),
+            LoadError::PgmUnexpectedEof => write!(f, "unexpected end of PGM pixel data"),
+            LoadError::PgmInvalidPixel => write!(f, "invalid PGM pixel value"),
+        }
+    }
+}
+
+impl error::Error for LoadError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            LoadError::Io(ref err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for LoadError {
+    fn from(err: io::Error) -> LoadError {
+        LoadError::Io(err)
+    }
+}
+
+// Ascii
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct AsciiLegend {
+    pub walls: Vec<u8>,
+    pub start: u8,
+    pub goal: u8,
+}
+
+impl Default for AsciiLegend {
+    fn default() -> AsciiLegend {
+        AsciiLegend { walls: vec![b'#'], start: b'*', goal: b'@', }
+    }
+}
+
+impl OccupancyGrid {
+    // lines may be of different length: cells past the end of a line are occupied
+    pub fn from_ascii_lines<I, L>(lines: I, legend: &AsciiLegend) -> Result<OccupancyGrid, LoadError>
+        where I: IntoIterator<Item = L>,
+              L: AsRef<[u8]>,
+    {
+        let lines: Vec<_> = lines.into_iter().collect();
+        let rows = lines.len();
+        let cols = lines.iter().map(|line| line.as_ref().len()).max().unwrap_or(0);
+        if rows == 0 || cols == 0 {
+            return Err(LoadError::EmptyMap);
+        }
+        let mut grid = OccupancyGrid::new(rows, cols);
+        for (row, line) in lines.iter().enumerate() {
+            let line = line.as_ref();
+            for col in 0 .. cols {
+                let cell = (row, col);
+                match line.get(col) {
+                    None =>
+                        grid.set_occupied(&cell, true),
+                    Some(ch) if legend.walls.contains(ch) =>
+                        grid.set_occupied(&cell, true),
+                    Some(&ch) if ch == legend.start => {
+                        if grid.start().is_some() {
+                            return Err(LoadError::MultipleStarts);
+                        }
+                        grid.set_start(Some(cell));
+                    },
+                    Some(&ch) if ch == legend.goal => {
+                        if grid.goal().is_some() {
+                            return Err(LoadError::MultipleGoals);
+                        }
+                        grid.set_goal(Some(cell));
+                    },
+                    Some(..) =>
+                        (),
+                }
+            }
+        }
+        Ok(grid)
+    }
+
+    pub fn from_ascii(text: &[u8], legend: &AsciiLegend) -> Result<OccupancyGrid, LoadError> {
+        let lines = text.split(|&ch| ch == b'\n')
+            .map(|line| if line.last() == Some(&b'\r') { &line[.. line.len() - 1] } else { line });
+        let mut lines: Vec<_> = lines.collect();
+        while lines.last().map(|line| line.is_empty()).unwrap_or(false) {
+            lines.pop();
+        }
+        OccupancyGrid::from_ascii_lines(lines, legend)
+    }
+
+    pub fn load_ascii<P>(path: P, legend: &AsciiLegend) -> Result<OccupancyGrid, LoadError> where P: AsRef<Path> {
+        let text = fs::read(path)?;
+        OccupancyGrid::from_ascii(&text, legend)
+    }
+}
+
+// Pgm
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct PgmThreshold {
+    // pixels darker than `occupied_below * maxval` are occupied
+    pub occupied_below: f64,
+}
+
+impl Default for PgmThreshold {
+    fn default() -> PgmThreshold {
+        PgmThreshold { occupied_below: 0.5, }
+    }
+}
+
+struct PgmReader<'a> {
+    data: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> PgmReader<'a> {
+    fn skip_whitespace_and_comments(&mut self) {
+        while let Some(&ch) = self.data.get(self.offset) {
+            if ch == b'#' {
+                while self.data.get(self.offset).map(|&ch| ch != b'\n').unwrap_or(false) {
+                    self.offset += 1;
+                }
+            } else if ch.is_ascii_whitespace() {
+                self.offset += 1;
+            } else {
+                break;
+            }
+        }
+    }
+
+    fn token(&mut self) -> Option<&'a [u8]> {
+        self.skip_whitespace_and_comments();
+        let begin = self.offset;
+        while self.data.get(self.offset).map(|ch| !ch.is_ascii_whitespace()).unwrap_or(false) {
+            self.offset += 1;
+        }
+        if begin == self.offset {
+            None
+        } else {
+            Some(&self.data[begin .. self.offset])
+        }
+    }
+
+    fn header_number(&mut self) -> Result<usize, LoadError> {
+        self.token()
+            .and_then(parse_number)
+            .ok_or(LoadError::PgmInvalidHeader)
+    }
+}
+
+fn parse_number(token: &[u8]) -> Option<usize> {
+    str::from_utf8(token).ok()
+        .and_then(|token| token.parse().ok())
+}
+
+impl OccupancyGrid {
+    // supports both ascii (`P2`) and binary (`P5`) graymaps, image row 0 becomes grid row 0
+    pub fn from_pgm(data: &[u8], threshold: &PgmThreshold) -> Result<OccupancyGrid, LoadError> {
+        let mut reader = PgmReader { data, offset: 0, };
+        let binary = match reader.token() {
+            Some(b"P2") => false,
+            Some(b"P5") => true,
+            _ => return Err(LoadError::PgmInvalidMagic),
+        };
+        let cols = reader.header_number()?;
+        let rows = reader.header_number()?;
+        let maxval = reader.header_number()?;
+        if maxval == 0 || maxval > 65535 {
+            return Err(LoadError::PgmInvalidHeader);
+        }
+        if rows == 0 || cols == 0 {
+            return Err(LoadError::EmptyMap);
+        }
+        if binary {
+            // exactly one whitespace character separates header from pixel data
+            reader.offset += 1;
+        }
+        // the header is untrusted: make sure the payload can hold every pixel before allocating the grid
+        let width = if maxval < 256 { 1 } else { 2 };
+        let min_payload = rows.checked_mul(cols)
+            .and_then(|pixels| if binary { pixels.checked_mul(width) } else { pixels.checked_mul(2).map(|bytes| bytes - 1) })
+            .ok_or(LoadError::PgmInvalidHeader)?;
+        if data.len().saturating_sub(reader.offset) < min_payload {
+            return Err(LoadError::PgmUnexpectedEof);
+        }
+        let level = threshold.occupied_below * maxval as f64;
+        let mut grid = OccupancyGrid::new(rows, cols);
+        for row in 0 .. rows {
+            for col in 0 .. cols {
+                let pixel = if binary {
+                    let bytes = data.get(reader.offset .. reader.offset + width)
+                        .ok_or(LoadError::PgmUnexpectedEof)?;
+                    reader.offset += width;
+                    bytes.iter().fold(0, |value, &byte| (value << 8) | byte as usize)
+                } else {
+                    let token = reader.token().ok_or(LoadError::PgmUnexpectedEof)?;
+                    parse_number(token).ok_or(LoadError::PgmInvalidPixel)?
+                };
+                if pixel > maxval {
+                    return Err(LoadError::PgmInvalidPixel);
+                }
+                grid.set_occupied(&(row, col), (pixel as f64) < level);
+            }
+        }
+        Ok(grid)
+    }
+
+    pub fn load_pgm<P>(path: P, threshold: &PgmThreshold) -> Result<OccupancyGrid, LoadError> where P: AsRef<Path> {
+        let data = fs::read(path)?;
+        OccupancyGrid::from_pgm(&data, threshold)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use util::validity::MotionValidator;
+
+    #[test]
+    fn ascii_map() {
+        let grid = OccupancyGrid::from_ascii(b"#####\r\n# * #\n#  @###\n#####\n\n", &AsciiLegend::default()).unwrap();
+        assert_eq!((grid.rows(), grid.cols()), (4, 7));
+        assert_eq!(grid.start(), Some((1, 2)));
+        assert_eq!(grid.goal(), Some((2, 3)));
+        assert!(grid.is_occupied(&(0, 0)));
+        assert!(grid.is_free(&(1, 1)));
+        assert!(grid.is_occupied(&(1, 5)));
+        assert!(grid.is_occupied(&(9, 9)));
+
+        let legend = AsciiLegend { walls: vec![b'X', b'='], start: b'S', goal: b'G', };
+        let grid = OccupancyGrid::from_ascii_lines(["XS=", "G  "], &legend).unwrap();
+        assert_eq!(grid.start(), Some((0, 1)));
+        assert_eq!(grid.goal(), Some((1, 0)));
+        assert!(grid.is_occupied(&(0, 2)));
+
+        match OccupancyGrid::from_ascii(b"**", &AsciiLegend::default()) {
+            Err(LoadError::MultipleStarts) => (),
+            other => panic!("unexpected result: {:?}", other),
+        }
+        match OccupancyGrid::from_pgm(b"P5 4000000000 4000000000\n255\n\0\0", &PgmThreshold::default()) {
+            Err(LoadError::PgmUnexpectedEof) | Err(LoadError::PgmInvalidHeader) => (),
+            other => panic!("unexpected result: {:?}", other),
+        }
+        match OccupancyGrid::from_pgm(b"P2 100000 100000 255 0 0 0", &PgmThreshold::default()) {
+            Err(LoadError::PgmUnexpectedEof) => (),
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn pgm_maps() {
+        let ascii = b"P2\n# comment\n3 2\n255\n0 255 200\n10 128 127\n";
+        let grid = OccupancyGrid::from_pgm(ascii, &PgmThreshold::default()).unwrap();
+        let occupied: Vec<_> = grid.cells().map(|(_, occupied)| occupied).collect();
+        assert_eq!(occupied, vec![true, false, false, true, false, true]);
+
+        let mut binary = b"P5 3 2\n65535\n".to_vec();
+        for &pixel in &[0u16, 65535, 40000, 100, 32768, 32767] {
+            binary.push((pixel >> 8) as u8);
+            binary.push(pixel as u8);
+        }
+        let grid = OccupancyGrid::from_pgm(&binary, &PgmThreshold::default()).unwrap();
+        let occupied: Vec<_> = grid.cells().map(|(_, occupied)| occupied).collect();
+        assert_eq!(occupied, vec![true, false, false, true, false, true]);
+
+        match OccupancyGrid::from_pgm(&binary[.. binary.len() - 1], &PgmThreshold::default()) {
+            Err(LoadError::PgmUnexpectedEof) => (),
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn world_frame() {
+        let mut grid = OccupancyGrid::new(4, 3);
+        grid.set_world_frame((-1.0, 2.0), 0.5);
+        assert_eq!(grid.world_to_cell(&[-1.0, 2.0]), Some((0, 0)));
+        assert_eq!(grid.world_to_cell(&[0.4, 3.9]), Some((3, 2)));
+        assert_eq!(grid.world_to_cell(&[0.6, 3.9]), None);
+        assert_eq!(grid.world_to_cell(&[-1.1, 3.0]), None);
+        assert_eq!(grid.world_to_cell(&[f64::NAN, 2.0]), None);
+        assert_eq!(grid.world_to_cell(&[-1.0, f64::INFINITY]), None);
+        assert_eq!(grid.cell_to_world(&(3, 2)), vec![0.25, 3.75]);
+    }
+
+    #[test]
+    fn non_finite_motion() {
+        let grid = OccupancyGrid::new(4, 4);
+        assert!(grid.is_motion_valid(&vec![0.5, 0.5], &vec![3.5, 2.5]));
+        assert!(!grid.is_motion_valid(&vec![0.5, 0.5], &vec![f64::NAN, f64::NAN]));
+        assert!(!grid.is_motion_valid(&vec![f64::INFINITY, 0.5], &vec![0.5, 0.5]));
+    }
+}
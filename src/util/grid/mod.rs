@@ -0,0 +1,163 @@
+use util::validity::{StateValidityChecker, MotionValidator};
+
+use self::traversal::{Supercover, SegmentCells};
+
+pub mod load;
+pub mod traversal;
+pub mod sdf;
+
+// (row, col)
+pub type Cell = (usize, usize);
+
+// OccupancyGrid
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct OccupancyGrid {
+    rows: usize,
+    cols: usize,
+    occupied: Vec<bool>,
+    origin: (f64, f64),
+    resolution: f64,
+    start: Option<Cell>,
+    goal: Option<Cell>,
+}
+
+impl OccupancyGrid {
+    pub fn new(rows: usize, cols: usize) -> OccupancyGrid {
+        OccupancyGrid {
+            rows, cols,
+            occupied: vec![false; rows * cols],
+            origin: (0.0, 0.0),
+            resolution: 1.0,
+            start: None,
+            goal: None,
+        }
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    pub fn contains(&self, &(row, col): &Cell) -> bool {
+        row < self.rows && col < self.cols
+    }
+
+    // cells outside of the grid are considered occupied
+    pub fn is_occupied(&self, cell: &Cell) -> bool {
+        if self.contains(cell) {
+            self.occupied[cell.0 * self.cols + cell.1]
+        } else {
+            true
+        }
+    }
+
+    pub fn is_free(&self, cell: &Cell) -> bool {
+        !self.is_occupied(cell)
+    }
+
+    pub fn set_occupied(&mut self, cell: &Cell, occupied: bool) {
+        assert!(self.contains(cell), "cell {:?} is out of grid bounds", cell);
+        self.occupied[cell.0 * self.cols + cell.1] = occupied;
+    }
+
+    pub fn start(&self) -> Option<Cell> {
+        self.start
+    }
+
+    pub fn set_start(&mut self, cell: Option<Cell>) {
+        self.start = cell;
+    }
+
+    pub fn goal(&self) -> Option<Cell> {
+        self.goal
+    }
+
+    pub fn set_goal(&mut self, cell: Option<Cell>) {
+        self.goal = cell;
+    }
+
+    pub fn cells(&self) -> impl Iterator<Item = (Cell, bool)> + '_ {
+        let cols = self.cols;
+        self.occupied.iter()
+            .enumerate()
+            .map(move |(index, &occupied)| ((index / cols, index % cols), occupied))
+    }
+
+    // World frame
+
+    pub fn origin(&self) -> (f64, f64) {
+        self.origin
+    }
+
+    pub fn resolution(&self) -> f64 {
+        self.resolution
+    }
+
+    // `origin` is world `(x, y)` of the corner of cell `(0, 0)`, `resolution` is the cell side length;
+    // `x` grows along columns and `y` grows along rows
+    pub fn set_world_frame(&mut self, origin: (f64, f64), resolution: f64) {
+        assert!(resolution > 0.0, "grid resolution should be positive");
+        self.origin = origin;
+        self.resolution = resolution;
+    }
+
+    pub fn world_to_cell(&self, point: &[f64]) -> Option<Cell> {
+        let col = ((point[0] - self.origin.0) / self.resolution).floor();
+        let row = ((point[1] - self.origin.1) / self.resolution).floor();
+        // also rejects NaN, which would otherwise cast to cell zero
+        if !(col >= 0.0 && row >= 0.0 && col.is_finite() && row.is_finite()) {
+            return None;
+        }
+        let cell = (row as usize, col as usize);
+        if self.contains(&cell) {
+            Some(cell)
+        } else {
+            None
+        }
+    }
+
+    pub fn cell_to_world(&self, &(row, col): &Cell) -> Vec<f64> {
+        vec![
+            self.origin.0 + (col as f64 + 0.5) * self.resolution,
+            self.origin.1 + (row as f64 + 0.5) * self.resolution,
+        ]
+    }
+}
+
+impl StateValidityChecker<Cell> for OccupancyGrid {
+    fn is_valid(&self, cell: &Cell) -> bool {
+        self.is_free(cell)
+    }
+}
+
+impl StateValidityChecker<Vec<f64>> for OccupancyGrid {
+    fn is_valid(&self, point: &Vec<f64>) -> bool {
+        self.world_to_cell(point)
+            .map(|cell| self.is_free(&cell))
+            .unwrap_or(false)
+    }
+}
+
+impl MotionValidator<Cell> for OccupancyGrid {
+    fn is_motion_valid(&self, from: &Cell, to: &Cell) -> bool {
+        Supercover::new(from, to).all(|cell| self.is_free(&cell))
+    }
+}
+
+impl MotionValidator<Vec<f64>> for OccupancyGrid {
+    fn is_motion_valid(&self, from: &Vec<f64>, to: &Vec<f64>) -> bool {
+        if !from.iter().chain(to.iter()).all(|v| v.is_finite()) {
+            return false;
+        }
+        let to_cell_units = |point: &Vec<f64>| [
+            (point[1] - self.origin.1) / self.resolution,
+            (point[0] - self.origin.0) / self.resolution,
+        ];
+        SegmentCells::new(&to_cell_units(from), &to_cell_units(to))
+            .all(|(row, col)| row >= 0 && col >= 0 && self.is_free(&(row as usize, col as usize)))
+    }
+}
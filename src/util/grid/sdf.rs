@@ -0,0 +1,272 @@
+use super::{Cell, OccupancyGrid};
+use util::space::{Metric, euclidean_distance};
+
+const FAR: f64 = 1e20;
+
+// SignedDistanceField
+
+// Exact euclidean distance between cell centers in world units: positive for free cells
+// (distance to the nearest occupied cell), negative for occupied ones (distance to the nearest free cell).
+// Cells outside of the grid count as occupied, as in `OccupancyGrid::is_occupied`; a fully occupied
+// grid has no free cells at all, so its distances are minus infinity.
+#[derive(Clone, Debug)]
+pub struct SignedDistanceField {
+    rows: usize,
+    cols: usize,
+    distances: Vec<f64>,
+    origin: (f64, f64),
+    resolution: f64,
+}
+
+impl SignedDistanceField {
+    pub fn new(grid: &OccupancyGrid) -> SignedDistanceField {
+        let (rows, cols) = (grid.rows(), grid.cols());
+        assert!(rows > 0 && cols > 0, "distance field of an empty grid");
+        let to_occupied = squared_distance_transform(rows, cols, true, |cell| grid.is_occupied(cell));
+        let to_free = squared_distance_transform(rows, cols, false, |cell| grid.is_free(cell));
+        let distances = to_occupied.iter()
+            .zip(to_free.iter())
+            .map(|(&occ, &free)| if free >= FAR {
+                -f64::INFINITY
+            } else {
+                (occ.sqrt() - free.sqrt()) * grid.resolution()
+            })
+            .collect();
+        SignedDistanceField { rows, cols, distances, origin: grid.origin(), resolution: grid.resolution(), }
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    pub fn cell_distance(&self, &(row, col): &Cell) -> f64 {
+        self.distances[row * self.cols + col]
+    }
+
+    // point in continuous cell units with cell centers on integer coordinates, clamped to the grid
+    fn lattice(&self, point: &[f64]) -> (f64, f64) {
+        let col = ((point[0] - self.origin.0) / self.resolution - 0.5).clamp(0.0, (self.cols - 1) as f64);
+        let row = ((point[1] - self.origin.1) / self.resolution - 0.5).clamp(0.0, (self.rows - 1) as f64);
+        (row, col)
+    }
+
+    fn corners(&self, row: f64, col: f64) -> ([f64; 4], f64, f64) {
+        let row_0 = (row.floor() as usize).min(self.rows.saturating_sub(2));
+        let col_0 = (col.floor() as usize).min(self.cols.saturating_sub(2));
+        let row_1 = (row_0 + 1).min(self.rows - 1);
+        let col_1 = (col_0 + 1).min(self.cols - 1);
+        let values = [
+            self.cell_distance(&(row_0, col_0)),
+            self.cell_distance(&(row_0, col_1)),
+            self.cell_distance(&(row_1, col_0)),
+            self.cell_distance(&(row_1, col_1)),
+        ];
+        (values, row - row_0 as f64, col - col_0 as f64)
+    }
+
+    // bilinearly interpolated signed distance at world point `[x, y]`
+    pub fn distance(&self, point: &[f64]) -> f64 {
+        let (row, col) = self.lattice(point);
+        let ([d00, d01, d10, d11], fy, fx) = self.corners(row, col);
+        if d00.is_infinite() {
+            return d00;
+        }
+        let top = d00 + (d01 - d00) * fx;
+        let bottom = d10 + (d11 - d10) * fx;
+        top + (bottom - top) * fy
+    }
+
+    // gradient `[d/dx, d/dy]` of the interpolated distance, points away from obstacles
+    pub fn gradient(&self, point: &[f64]) -> [f64; 2] {
+        let (row, col) = self.lattice(point);
+        let ([d00, d01, d10, d11], fy, fx) = self.corners(row, col);
+        if d00.is_infinite() {
+            return [0.0, 0.0];
+        }
+        let d_dx = ((d01 - d00) * (1.0 - fy) + (d11 - d10) * fy) / self.resolution;
+        let d_dy = ((d10 - d00) * (1.0 - fx) + (d11 - d01) * fx) / self.resolution;
+        [d_dx, d_dy]
+    }
+}
+
+// Computed over the grid padded with a one cell ring of `border_is_feature` cells, then cropped back.
+fn squared_distance_transform<F>(rows: usize, cols: usize, border_is_feature: bool, is_feature: F) -> Vec<f64>
+    where F: Fn(&Cell) -> bool
+{
+    let padded = padded_squared_distance_transform(rows + 2, cols + 2, |&(row, col)| {
+        if row == 0 || col == 0 || row > rows || col > cols {
+            border_is_feature
+        } else {
+            is_feature(&(row - 1, col - 1))
+        }
+    });
+    (0 .. rows)
+        .flat_map(|row| padded[(row + 1) * (cols + 2) + 1 .. (row + 1) * (cols + 2) + 1 + cols].iter().cloned())
+        .collect()
+}
+
+fn padded_squared_distance_transform<F>(rows: usize, cols: usize, is_feature: F) -> Vec<f64> where F: Fn(&Cell) -> bool {
+    let mut field: Vec<f64> = (0 .. rows * cols)
+        .map(|index| if is_feature(&(index / cols, index % cols)) { 0.0 } else { FAR })
+        .collect();
+    let mut input = vec![0.0; rows.max(cols)];
+    let mut output = vec![0.0; rows.max(cols)];
+    for col in 0 .. cols {
+        for row in 0 .. rows {
+            input[row] = field[row * cols + col];
+        }
+        distance_transform_1d(&input[.. rows], &mut output[.. rows]);
+        for row in 0 .. rows {
+            field[row * cols + col] = output[row];
+        }
+    }
+    for row in 0 .. rows {
+        input[.. cols].copy_from_slice(&field[row * cols .. (row + 1) * cols]);
+        distance_transform_1d(&input[.. cols], &mut field[row * cols .. (row + 1) * cols]);
+    }
+    field
+}
+
+// Felzenszwalb & Huttenlocher lower envelope of parabolas
+fn distance_transform_1d(f: &[f64], d: &mut [f64]) {
+    let n = f.len();
+    if n == 0 {
+        return;
+    }
+    let mut v = vec![0usize; n];
+    let mut z = vec![0.0; n + 1];
+    let mut k = 0;
+    z[0] = -f64::INFINITY;
+    z[1] = f64::INFINITY;
+    for q in 1 .. n {
+        let qf = q as f64;
+        let intersection = |k: usize| {
+            let vk = v[k] as f64;
+            ((f[q] + qf * qf) - (f[v[k]] + vk * vk)) / (2.0 * qf - 2.0 * vk)
+        };
+        // `z[0]` is minus infinity so the loop always stops
+        let mut s = intersection(k);
+        while s <= z[k] {
+            k -= 1;
+            s = intersection(k);
+        }
+        k += 1;
+        v[k] = q;
+        z[k] = s;
+        z[k + 1] = f64::INFINITY;
+    }
+    k = 0;
+    for (q, value) in d.iter_mut().enumerate() {
+        let qf = q as f64;
+        while z[k + 1] < qf {
+            k += 1;
+        }
+        let offset = qf - v[k] as f64;
+        *value = offset * offset + f[v[k]];
+    }
+}
+
+// ClearanceCost
+
+// Edge cost growing when the edge passes close to obstacles:
+// `length * (1 + weight / max(clearance, min_clearance))` integrated along the edge.
+pub struct ClearanceCost<'a> {
+    sdf: &'a SignedDistanceField,
+    weight: f64,
+    min_clearance: f64,
+}
+
+impl<'a> ClearanceCost<'a> {
+    pub fn new(sdf: &'a SignedDistanceField, weight: f64, min_clearance: f64) -> ClearanceCost<'a> {
+        assert!(min_clearance > 0.0, "minimum clearance should be positive");
+        ClearanceCost { sdf, weight, min_clearance, }
+    }
+
+    pub fn edge_cost(&self, from: &[f64], to: &[f64]) -> f64 {
+        let length = euclidean_distance(from, to);
+        let steps = ((length / (self.sdf.resolution / 2.0)).ceil() as usize).max(1);
+        let penalty = (0 .. steps)
+            .map(|step| {
+                // midpoint rule
+                let t = (step as f64 + 0.5) / steps as f64;
+                let point = [from[0] + (to[0] - from[0]) * t, from[1] + (to[1] - from[1]) * t];
+                self.weight / self.sdf.distance(&point).max(self.min_clearance)
+            })
+            .sum::<f64>() / steps as f64;
+        length * (1.0 + penalty)
+    }
+}
+
+impl<'a> Metric<Vec<f64>> for ClearanceCost<'a> {
+    fn distance(&self, state_a: &Vec<f64>, state_b: &Vec<f64>) -> f64 {
+        self.edge_cost(state_a, state_b)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use util::grid::load::AsciiLegend;
+
+    #[test]
+    fn exact_transform() {
+        let grid = OccupancyGrid::from_ascii(b"\
+#######
+#     #
+#  #  #
+#     #
+#     #
+#######
+", &AsciiLegend::default()).unwrap();
+        let sdf = SignedDistanceField::new(&grid);
+        for ((row, col), occupied) in grid.cells() {
+            let brute = grid.cells()
+                .filter(|&(_, other)| other != occupied)
+                .map(|((r, c), _)| ((r as f64 - row as f64).powi(2) + (c as f64 - col as f64).powi(2)).sqrt())
+                .fold(f64::INFINITY, f64::min);
+            let expected = if occupied { -brute } else { brute };
+            assert!((sdf.cell_distance(&(row, col)) - expected).abs() < 1e-9, "at {:?}", (row, col));
+        }
+        assert_eq!(sdf.cell_distance(&(3, 3)), 1.0);
+        assert_eq!(sdf.cell_distance(&(2, 3)), -1.0);
+        assert!((sdf.distance(&[3.5, 2.0]) - 0.0).abs() < 1e-9);
+        let gradient = sdf.gradient(&[3.5, 3.0]);
+        assert!(gradient[1] > 0.0);
+
+        let cost = ClearanceCost::new(&sdf, 1.0, 0.1);
+        let near = cost.edge_cost(&[1.5, 2.5], &[5.5, 2.5]);
+        let far = cost.edge_cost(&[1.5, 4.5], &[5.5, 4.5]);
+        assert!(near > far && far > 4.0);
+    }
+
+    #[test]
+    fn grid_border() {
+        let open = OccupancyGrid::new(3, 5);
+        let sdf = SignedDistanceField::new(&open);
+        assert_eq!(sdf.cell_distance(&(1, 2)), 2.0);
+        assert_eq!(sdf.cell_distance(&(0, 0)), 1.0);
+        assert_eq!(sdf.cell_distance(&(2, 4)), 1.0);
+        assert!(sdf.distance(&[2.5, 1.5]).is_finite());
+
+        let mut solid = OccupancyGrid::new(2, 2);
+        for row in 0 .. 2 {
+            for col in 0 .. 2 {
+                solid.set_occupied(&(row, col), true);
+            }
+        }
+        let sdf = SignedDistanceField::new(&solid);
+        assert_eq!(sdf.cell_distance(&(1, 1)), -f64::INFINITY);
+        assert_eq!(sdf.distance(&[1.0, 1.0]), -f64::INFINITY);
+        assert_eq!(sdf.gradient(&[1.0, 1.0]), [0.0, 0.0]);
+    }
+
+    #[test]
+    #[should_panic(expected = "empty grid")]
+    fn empty_grid() {
+        SignedDistanceField::new(&OccupancyGrid::new(0, 4));
+    }
+}
@@ -0,0 +1,255 @@
+use std::collections::VecDeque;
+
+use super::Cell;
+
+// (row, col, layer)
+pub type Voxel = (usize, usize, usize);
+
+const TIE_EPSILON: f64 = 1e-9;
+
+// Dda
+
+// Amanatides-Woo traversal over an integer lattice of unit cells in any dimension.
+// When the segment passes exactly through a shared edge or corner all the touching
+// cells are reported, so the result is a supercover of the segment. A segment with a
+// non-finite coordinate has no cells to report and gives an empty traversal.
+#[derive(Clone, Debug)]
+struct Dda {
+    cell: Vec<isize>,
+    end: Vec<isize>,
+    step: Vec<isize>,
+    t_max: Vec<f64>,
+    t_delta: Vec<f64>,
+    pending: VecDeque<Vec<isize>>,
+    started: bool,
+    finished: bool,
+}
+
+impl Dda {
+    fn new(from: &[f64], to: &[f64]) -> Dda {
+        let dimension = from.len();
+        let mut dda = Dda {
+            cell: from.iter().map(|v| v.floor() as isize).collect(),
+            end: to.iter().map(|v| v.floor() as isize).collect(),
+            step: Vec::with_capacity(dimension),
+            t_max: Vec::with_capacity(dimension),
+            t_delta: Vec::with_capacity(dimension),
+            pending: VecDeque::new(),
+            started: false,
+            finished: false,
+        };
+        for axis in 0 .. dimension {
+            let delta = to[axis] - from[axis];
+            let (step, t_max, t_delta) = if delta > 0.0 {
+                (1, (from[axis].floor() + 1.0 - from[axis]) / delta, 1.0 / delta)
+            } else if delta < 0.0 {
+                (-1, (from[axis] - from[axis].floor()) / -delta, 1.0 / -delta)
+            } else {
+                (0, f64::INFINITY, f64::INFINITY)
+            };
+            dda.step.push(step);
+            dda.t_max.push(t_max);
+            dda.t_delta.push(t_delta);
+        }
+        if !from.iter().chain(to.iter()).all(|v| v.is_finite()) {
+            dda.started = true;
+            dda.finished = true;
+        }
+        dda
+    }
+
+    fn advance(&mut self) {
+        let t_min = self.t_max.iter().cloned().fold(f64::INFINITY, f64::min);
+        if self.cell == self.end || t_min > 1.0 + TIE_EPSILON {
+            self.finished = true;
+            return;
+        }
+        let tied: Vec<_> = (0 .. self.cell.len())
+            .filter(|&axis| self.t_max[axis] <= t_min + TIE_EPSILON)
+            .collect();
+        // all the cells sharing the crossed edge or corner, the full step comes last
+        let mut masks: Vec<usize> = (1 .. 1 << tied.len()).collect();
+        masks.sort_by_key(|mask| mask.count_ones());
+        for mask in masks {
+            let mut cell = self.cell.clone();
+            for (bit, &axis) in tied.iter().enumerate() {
+                if mask & (1 << bit) != 0 {
+                    cell[axis] += self.step[axis];
+                }
+            }
+            self.pending.push_back(cell);
+        }
+        for &axis in tied.iter() {
+            self.cell[axis] += self.step[axis];
+            self.t_max[axis] += self.t_delta[axis];
+        }
+    }
+
+    fn next_cell(&mut self) -> Option<Vec<isize>> {
+        if !self.started {
+            self.started = true;
+            return Some(self.cell.clone());
+        }
+        loop {
+            if let Some(cell) = self.pending.pop_front() {
+                return Some(cell);
+            }
+            if self.finished {
+                return None;
+            }
+            self.advance();
+        }
+    }
+}
+
+// Supercover
+
+#[derive(Clone, Debug)]
+pub struct Supercover {
+    dda: Dda,
+}
+
+impl Supercover {
+    // traverses the segment between cell centers, both endpoints are included
+    pub fn new(&(from_row, from_col): &Cell, &(to_row, to_col): &Cell) -> Supercover {
+        Supercover {
+            dda: Dda::new(
+                &[from_row as f64 + 0.5, from_col as f64 + 0.5],
+                &[to_row as f64 + 0.5, to_col as f64 + 0.5],
+            ),
+        }
+    }
+}
+
+impl Iterator for Supercover {
+    type Item = Cell;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.dda.next_cell().map(|cell| (cell[0] as usize, cell[1] as usize))
+    }
+}
+
+// SegmentCells
+
+// Traverses a segment with arbitrary real endpoints given in cell units as `[row, col]`,
+// cells may be negative when the segment leaves the grid. The endpoints should be finite,
+// otherwise no cells are reported.
+#[derive(Clone, Debug)]
+pub struct SegmentCells {
+    dda: Dda,
+}
+
+impl SegmentCells {
+    pub fn new(from: &[f64; 2], to: &[f64; 2]) -> SegmentCells {
+        SegmentCells { dda: Dda::new(from, to), }
+    }
+}
+
+impl Iterator for SegmentCells {
+    type Item = (isize, isize);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.dda.next_cell().map(|cell| (cell[0], cell[1]))
+    }
+}
+
+// VoxelSupercover
+
+#[derive(Clone, Debug)]
+pub struct VoxelSupercover {
+    dda: Dda,
+}
+
+impl VoxelSupercover {
+    // traverses the segment between voxel centers, both endpoints are included
+    pub fn new(from: &Voxel, to: &Voxel) -> VoxelSupercover {
+        VoxelSupercover {
+            dda: Dda::new(
+                &[from.0 as f64 + 0.5, from.1 as f64 + 0.5, from.2 as f64 + 0.5],
+                &[to.0 as f64 + 0.5, to.1 as f64 + 0.5, to.2 as f64 + 0.5],
+            ),
+        }
+    }
+}
+
+impl Iterator for VoxelSupercover {
+    type Item = Voxel;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.dda.next_cell().map(|cell| (cell[0] as usize, cell[1] as usize, cell[2] as usize))
+    }
+}
+
+// SegmentVoxels
+
+// Traverses a segment with arbitrary real endpoints given in voxel units, voxels may be negative.
+// The endpoints should be finite, otherwise no voxels are reported.
+#[derive(Clone, Debug)]
+pub struct SegmentVoxels {
+    dda: Dda,
+}
+
+impl SegmentVoxels {
+    pub fn new(from: &[f64; 3], to: &[f64; 3]) -> SegmentVoxels {
+        SegmentVoxels { dda: Dda::new(from, to), }
+    }
+}
+
+impl Iterator for SegmentVoxels {
+    type Item = (isize, isize, isize);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.dda.next_cell().map(|cell| (cell[0], cell[1], cell[2]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn axis_aligned() {
+        let cells: Vec<_> = Supercover::new(&(2, 5), &(2, 2)).collect();
+        assert_eq!(cells, vec![(2, 5), (2, 4), (2, 3), (2, 2)]);
+        let cells: Vec<_> = Supercover::new(&(1, 1), &(1, 1)).collect();
+        assert_eq!(cells, vec![(1, 1)]);
+    }
+
+    #[test]
+    fn exact_diagonal() {
+        let cells: Vec<_> = Supercover::new(&(0, 0), &(2, 2)).collect();
+        assert_eq!(cells, vec![(0, 0), (1, 0), (0, 1), (1, 1), (2, 1), (1, 2), (2, 2)]);
+    }
+
+    #[test]
+    fn arbitrary_direction() {
+        let cells: Vec<_> = Supercover::new(&(0, 0), &(1, 4)).collect();
+        assert_eq!(cells, vec![(0, 0), (0, 1), (0, 2), (1, 2), (1, 3), (1, 4)]);
+        // no corners are crossed exactly here, so every step moves into a 4-neighbour
+        let cells: Vec<_> = Supercover::new(&(7, 1), &(0, 13)).collect();
+        for pair in cells.windows(2) {
+            let ((r0, c0), (r1, c1)) = (pair[0], pair[1]);
+            assert_eq!((r0 as isize - r1 as isize).abs() + (c0 as isize - c1 as isize).abs(), 1);
+        }
+        assert_eq!(cells.first(), Some(&(7, 1)));
+        assert_eq!(cells.last(), Some(&(0, 13)));
+    }
+
+    #[test]
+    fn continuous_segments() {
+        let cells: Vec<_> = SegmentCells::new(&[0.5, 0.2], &[-0.5, 0.2]).collect();
+        assert_eq!(cells, vec![(0, 0), (-1, 0)]);
+        let voxels: Vec<_> = VoxelSupercover::new(&(0, 0, 0), &(1, 1, 1)).collect();
+        assert_eq!(voxels.len(), 8);
+        assert_eq!(voxels.last(), Some(&(1, 1, 1)));
+        let voxels: Vec<_> = SegmentVoxels::new(&[0.1, 0.1, 0.1], &[2.9, 0.2, 0.3]).collect();
+        assert_eq!(voxels, vec![(0, 0, 0), (1, 0, 0), (2, 0, 0)]);
+    }
+
+    #[test]
+    fn non_finite_segments() {
+        assert_eq!(SegmentCells::new(&[0.5, 0.5], &[f64::NAN, 2.0]).count(), 0);
+        assert_eq!(SegmentCells::new(&[f64::NEG_INFINITY, 0.5], &[0.5, 0.5]).count(), 0);
+        assert_eq!(SegmentVoxels::new(&[0.5, 0.5, 0.5], &[1.5, f64::INFINITY, 0.5]).count(), 0);
+    }
+}
@@ -1,4 +1,23 @@
 pub mod rtt;
+pub mod space;
+pub mod sample;
+pub mod validity;
+pub mod grid;
+pub mod geom2d;
+pub mod geom3d;
+pub mod path;
+pub mod nearest;
+pub mod goal;
+pub mod prm;
+#[cfg(test)]
+pub mod testing;
 
+// Uninhabited, so `_ok` transitions are statically proven to never fail.
 // TODO: switch to bang `!` after https://github.com/rust-lang/rust/issues/35121
-pub type NeverError = ();
+pub type NeverError = ::std::convert::Infallible;
+
+// Compatibility for transitions written against the former `NeverError = ()`: wrap their results
+// with this function to use them with `_ok` methods, an actual `Err(())` panics.
+pub fn from_unit_error<T>(result: Result<T, ()>) -> Result<T, NeverError> {
+    result.map_err(|()| panic!("transition declared as never failing returned an error"))
+}
@@ -0,0 +1,44 @@
+// Nearest neighbour queries shared by the trees and the roadmap. Implementors only
+// enumerate their states, the queries are linear scans over them.
+pub trait NearestNeighbours<S> {
+    type Ref: Copy;
+
+    fn visit_states<F>(&self, visit: F) where F: FnMut(Self::Ref, &S);
+
+    // of equally close states the first one visited wins
+    fn nearest<F>(&self, mut distance: F) -> Option<(Self::Ref, f64)> where F: FnMut(&S) -> f64 {
+        let mut closest: Option<(Self::Ref, f64)> = None;
+        self.visit_states(|state_ref, state| {
+            let state_distance = distance(state);
+            let better = match closest {
+                None => true,
+                Some((_, best_distance)) => state_distance < best_distance,
+            };
+            if better {
+                closest = Some((state_ref, state_distance));
+            }
+        });
+        closest
+    }
+
+    // ordered by distance, ties keep the visiting order
+    fn k_nearest<F>(&self, k: usize, mut distance: F) -> Vec<(Self::Ref, f64)> where F: FnMut(&S) -> f64 {
+        let mut near = Vec::new();
+        self.visit_states(|state_ref, state| near.push((state_ref, distance(state))));
+        near.sort_by(|a, b| a.1.total_cmp(&b.1));
+        near.truncate(k);
+        near
+    }
+
+    // in the visiting order
+    fn within<F>(&self, radius: f64, mut distance: F) -> Vec<(Self::Ref, f64)> where F: FnMut(&S) -> f64 {
+        let mut near = Vec::new();
+        self.visit_states(|state_ref, state| {
+            let state_distance = distance(state);
+            if state_distance <= radius {
+                near.push((state_ref, state_distance));
+            }
+        });
+        near
+    }
+}
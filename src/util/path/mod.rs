@@ -0,0 +1,183 @@
+use std::ops::Range;
+use std::vec;
+
+use util::space::{Metric, Interpolation};
+use util::validity::MotionValidator;
+use util::rtt::vec_slist::RevPathIterator;
+
+pub mod shortcut;
+pub mod spline;
+pub mod trajectory;
+
+pub fn path_cost<S, M>(states: &[S], metric: &M) -> f64 where M: Metric<S> {
+    states.windows(2)
+        .map(|pair| metric.distance(&pair[0], &pair[1]))
+        .sum()
+}
+
+// Path
+
+// Sequence of states in forward order, from the start to the end.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Path<S> {
+    states: Vec<S>,
+}
+
+impl<S> Path<S> {
+    pub fn new(states: Vec<S>) -> Path<S> {
+        Path { states, }
+    }
+
+    pub fn from_rev_states<I>(rev_states: I) -> Path<S> where I: IntoIterator<Item = S> {
+        let mut states: Vec<_> = rev_states.into_iter().collect();
+        states.reverse();
+        Path { states, }
+    }
+
+    pub fn states(&self) -> &[S] {
+        &self.states
+    }
+
+    pub fn states_mut(&mut self) -> &mut Vec<S> {
+        &mut self.states
+    }
+
+    pub fn into_states(self) -> Vec<S> {
+        self.states
+    }
+
+    pub fn len(&self) -> usize {
+        self.states.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.states.is_empty()
+    }
+
+    pub fn first(&self) -> Option<&S> {
+        self.states.first()
+    }
+
+    pub fn last(&self) -> Option<&S> {
+        self.states.last()
+    }
+
+    pub fn cost<M>(&self, metric: &M) -> f64 where M: Metric<S> {
+        path_cost(&self.states, metric)
+    }
+
+    pub fn reverse(&mut self) {
+        self.states.reverse();
+    }
+
+    pub fn reversed(mut self) -> Path<S> {
+        self.reverse();
+        self
+    }
+
+    // junction state shared by both paths (e.g. the meeting point of a bidirectional planner) is kept once
+    pub fn concat(mut self, other: Path<S>) -> Path<S> where S: PartialEq {
+        let mut other = other.states.into_iter().peekable();
+        if self.states.last().is_some() && self.states.last() == other.peek() {
+            other.next();
+        }
+        self.states.extend(other);
+        self
+    }
+
+    pub fn subpath(&self, range: Range<usize>) -> Path<S> where S: Clone {
+        Path { states: self.states[range].to_vec(), }
+    }
+
+    pub fn first_invalid_motion<MV>(&self, validator: &MV) -> Option<usize> where MV: MotionValidator<S> {
+        self.states.windows(2)
+            .position(|pair| !validator.is_motion_valid(&pair[0], &pair[1]))
+    }
+
+    pub fn is_valid<MV>(&self, validator: &MV) -> bool where MV: MotionValidator<S> {
+        self.first_invalid_motion(validator).is_none()
+    }
+
+    // `segments + 1` states equally spaced by arc length measured with the space metric
+    pub fn resample<SP>(&self, space: &SP, segments: usize) -> Path<S> where SP: Metric<S> + Interpolation<S>, S: Clone {
+        assert!(segments > 0, "at least one segment required");
+        if self.states.len() < 2 {
+            return self.clone();
+        }
+        let lengths: Vec<f64> = self.states.windows(2)
+            .map(|pair| space.distance(&pair[0], &pair[1]))
+            .collect();
+        let total: f64 = lengths.iter().sum();
+        let mut resampled = Vec::with_capacity(segments + 1);
+        resampled.push(self.states[0].clone());
+        let (mut index, mut passed) = (0, 0.0);
+        for step in 1 .. segments {
+            let target = total * step as f64 / segments as f64;
+            while index + 1 < lengths.len() && passed + lengths[index] < target {
+                passed += lengths[index];
+                index += 1;
+            }
+            let t = if lengths[index] > 0.0 { ((target - passed) / lengths[index]).clamp(0.0, 1.0) } else { 0.0 };
+            resampled.push(space.interpolate(&self.states[index], &self.states[index + 1], t));
+        }
+        resampled.push(self.states[self.states.len() - 1].clone());
+        Path { states: resampled, }
+    }
+}
+
+impl<S> From<Vec<S>> for Path<S> {
+    fn from(states: Vec<S>) -> Path<S> {
+        Path::new(states)
+    }
+}
+
+impl<S> From<RevPathIterator<S>> for Path<S> {
+    fn from(rev_path: RevPathIterator<S>) -> Path<S> {
+        Path::from_rev_states(rev_path)
+    }
+}
+
+impl<S> IntoIterator for Path<S> {
+    type Item = S;
+    type IntoIter = vec::IntoIter<S>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.states.into_iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use util::space::Euclidean;
+    use util::rtt::vec_slist::EmptyRandomTree;
+
+    #[test]
+    fn path_operations() {
+        let mut rtt = EmptyRandomTree::new().add_root(vec![0.0, 0.0]);
+        let root = rtt.root();
+        let node = rtt.expand(root, vec![3.0, 0.0]);
+        let node = rtt.expand(node, vec![3.0, 4.0]);
+        let path = rtt.forward_path(&node);
+        assert_eq!(path.states(), &[vec![0.0, 0.0], vec![3.0, 0.0], vec![3.0, 4.0]]);
+        assert_eq!(path.cost(&Euclidean), 7.0);
+
+        let resampled = path.resample(&Euclidean, 7);
+        assert_eq!(resampled.len(), 8);
+        assert_eq!(resampled.states()[2], vec![2.0, 0.0]);
+        assert_eq!(resampled.states()[5], vec![3.0, 2.0]);
+        assert_eq!(resampled.cost(&Euclidean), 7.0);
+
+        let back = Path::new(vec![vec![3.0, 4.0], vec![0.0, 4.0]]);
+        let joined = path.clone().concat(back);
+        assert_eq!(joined.len(), 4);
+        assert_eq!(joined.cost(&Euclidean), 10.0);
+        assert_eq!(joined.subpath(1 .. 3).states(), &[vec![3.0, 0.0], vec![3.0, 4.0]]);
+        assert_eq!(joined.clone().reversed().first(), Some(&vec![0.0, 4.0]));
+
+        let wall = |from: &Vec<f64>, to: &Vec<f64>| from[1].max(to[1]) < 3.0;
+        assert_eq!(joined.first_invalid_motion(&wall), Some(1));
+        assert!(!joined.is_valid(&wall));
+        assert_eq!(Path::from(rtt.into_path(node)), path);
+    }
+}
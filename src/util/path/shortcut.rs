@@ -0,0 +1,180 @@
+use super::path_cost;
+use util::space::Metric;
+use util::sample::RandomSource;
+use util::validity::MotionValidator;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct SimplifyReport {
+    pub cost_before: f64,
+    pub cost_after: f64,
+    pub vertices_before: usize,
+    pub vertices_after: usize,
+}
+
+// PathSimplifier
+
+// Post-processes paths given in forward order (e.g. reversed `RandomTree::into_path` output).
+// Every modification is checked with the motion validator and accepted only when it does not
+// increase the path cost, so a valid path stays valid.
+pub struct PathSimplifier<'a, MV: 'a, M, R> {
+    validator: &'a MV,
+    metric: M,
+    rng: R,
+}
+
+impl<'a, MV, M, R> PathSimplifier<'a, MV, M, R> where R: RandomSource {
+    pub fn new(validator: &'a MV, metric: M, rng: R) -> PathSimplifier<'a, MV, M, R> {
+        PathSimplifier { validator, metric, rng, }
+    }
+
+    fn random_index(&mut self, upper: usize) -> usize {
+        ((self.rng.next_unit() * upper as f64) as usize).min(upper - 1)
+    }
+
+    // tries to connect two random vertices directly dropping everything between them
+    pub fn random_shortcut<S>(&mut self, path: &mut Vec<S>, attempts: usize) -> usize
+        where MV: MotionValidator<S>, M: Metric<S>
+    {
+        let mut removed = 0;
+        for _ in 0 .. attempts {
+            if path.len() < 3 {
+                break;
+            }
+            let (a, b) = (self.random_index(path.len()), self.random_index(path.len()));
+            let (from, to) = (a.min(b), a.max(b));
+            if to - from < 2 {
+                continue;
+            }
+            let direct = self.metric.distance(&path[from], &path[to]);
+            if direct <= path_cost(&path[from ..= to], &self.metric) &&
+                self.validator.is_motion_valid(&path[from], &path[to])
+            {
+                path.drain(from + 1 .. to);
+                removed += to - from - 1;
+            }
+        }
+        removed
+    }
+
+    // walks from the start connecting each kept vertex to the farthest reachable one
+    pub fn reduce_vertices<S>(&mut self, path: &mut Vec<S>) -> usize
+        where MV: MotionValidator<S>, M: Metric<S>
+    {
+        let before = path.len();
+        let mut from = 0;
+        while from + 2 < path.len() {
+            let reachable = (from + 2 .. path.len()).rev()
+                .find(|&to| {
+                    self.metric.distance(&path[from], &path[to]) <= path_cost(&path[from ..= to], &self.metric) &&
+                        self.validator.is_motion_valid(&path[from], &path[to])
+                });
+            if let Some(to) = reachable {
+                path.drain(from + 1 .. to);
+            }
+            from += 1;
+        }
+        before - path.len()
+    }
+
+    // straightens a single random dimension between two random vertices keeping the others intact
+    pub fn partial_shortcut(&mut self, path: &mut [Vec<f64>], attempts: usize) -> usize
+        where MV: MotionValidator<Vec<f64>>, M: Metric<Vec<f64>>
+    {
+        let mut accepted = 0;
+        for _ in 0 .. attempts {
+            if path.len() < 3 || path[0].is_empty() {
+                break;
+            }
+            let (a, b) = (self.random_index(path.len()), self.random_index(path.len()));
+            let (from, to) = (a.min(b), a.max(b));
+            if to - from < 2 {
+                continue;
+            }
+            let dimension = self.random_index(path[0].len());
+            let lengths: Vec<f64> = path[from ..= to].windows(2)
+                .map(|pair| self.metric.distance(&pair[0], &pair[1]))
+                .collect();
+            let total: f64 = lengths.iter().sum();
+            if total <= 0.0 {
+                continue;
+            }
+            let (start_value, end_value) = (path[from][dimension], path[to][dimension]);
+            let mut candidate = path[from ..= to].to_vec();
+            let mut travelled = 0.0;
+            for (index, state) in candidate.iter_mut().enumerate().skip(1).take(to - from - 1) {
+                travelled += lengths[index - 1];
+                state[dimension] = start_value + (end_value - start_value) * travelled / total;
+            }
+            if path_cost(&candidate, &self.metric) < total &&
+                candidate.windows(2).all(|pair| self.validator.is_motion_valid(&pair[0], &pair[1]))
+            {
+                path[from ..= to].clone_from_slice(&candidate);
+                accepted += 1;
+            }
+        }
+        accepted
+    }
+
+    pub fn simplify<S>(&mut self, path: &mut Vec<S>, attempts: usize) -> SimplifyReport
+        where MV: MotionValidator<S>, M: Metric<S>
+    {
+        let cost_before = path_cost(path, &self.metric);
+        let vertices_before = path.len();
+        self.reduce_vertices(path);
+        self.random_shortcut(path, attempts);
+        self.reduce_vertices(path);
+        SimplifyReport {
+            cost_before,
+            cost_after: path_cost(path, &self.metric),
+            vertices_before,
+            vertices_after: path.len(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use util::space::Euclidean;
+    use util::geom2d::{ObstacleWorld, Aabb, Shape};
+    use util::testing::xorshift;
+
+    #[test]
+    fn simplify_around_wall() {
+        let mut world = ObstacleWorld::new(Aabb::new([0.0, 0.0], [10.0, 10.0]), 2.0);
+        world.add_obstacle(Shape::Box(Aabb::new([4.0, 0.0], [6.0, 6.0])));
+        let mut path: Vec<Vec<f64>> = vec![
+            vec![1.0, 1.0], vec![2.0, 3.0], vec![1.0, 5.0], vec![2.0, 7.0], vec![4.0, 8.0],
+            vec![5.0, 7.0], vec![6.0, 8.0], vec![8.0, 7.0], vec![9.0, 4.0], vec![8.0, 2.0], vec![9.0, 1.0],
+        ];
+        let mut simplifier = PathSimplifier::new(&world, Euclidean, xorshift(5));
+        let report = simplifier.simplify(&mut path, 100);
+        assert!(report.cost_after < report.cost_before);
+        assert!(report.vertices_after < report.vertices_before);
+        assert_eq!(path.first(), Some(&vec![1.0, 1.0]));
+        assert_eq!(path.last(), Some(&vec![9.0, 1.0]));
+        assert!(path.windows(2).all(|pair| world.is_motion_valid(&pair[0], &pair[1])));
+
+        let cost = path_cost(&path, &Euclidean);
+        simplifier.partial_shortcut(&mut path, 100);
+        assert!(path_cost(&path, &Euclidean) <= cost);
+        assert!(path.windows(2).all(|pair| world.is_motion_valid(&pair[0], &pair[1])));
+    }
+
+    #[test]
+    fn partial_shortcut_flattens_zigzag() {
+        let world = ObstacleWorld::new(Aabb::new([-10.0, -10.0], [10.0, 10.0]), 2.0);
+        let mut path: Vec<Vec<f64>> = vec![
+            vec![0.0, 0.0], vec![1.0, 1.0], vec![2.0, -1.0], vec![3.0, 1.0], vec![4.0, -1.0], vec![5.0, 0.0],
+        ];
+        // the straight line along the x axis costs 5.0
+        let cost = path_cost(&path, &Euclidean);
+        let mut simplifier = PathSimplifier::new(&world, Euclidean, xorshift(9));
+        let accepted = simplifier.partial_shortcut(&mut path, 100);
+        assert!(accepted > 0);
+        assert!(path_cost(&path, &Euclidean) < cost - 1.0);
+        assert_eq!(path.len(), 6);
+        assert_eq!(path.first(), Some(&vec![0.0, 0.0]));
+        assert_eq!(path.last(), Some(&vec![5.0, 0.0]));
+    }
+}
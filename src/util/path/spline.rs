@@ -0,0 +1,272 @@
+use util::validity::MotionValidator;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum SplineKind {
+    // uniform cubic b-spline using path vertices as the control polygon, clamped at the endpoints
+    BSpline,
+    // cubic hermite interpolating path vertices with catmull-rom tangents scaled by `tension`
+    Hermite { tension: f64, },
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct SmoothedPath {
+    pub states: Vec<Vec<f64>>,
+    pub max_curvature: f64,
+    pub iterations: usize,
+    // `false` when no valid spline was found and the original polyline is returned
+    pub smoothed: bool,
+}
+
+impl SmoothedPath {
+    pub fn within_curvature(&self, limit: f64) -> bool {
+        self.max_curvature <= limit
+    }
+}
+
+// SplineSmoother
+
+// Fits a spline over a valid polyline path and re-checks it with the motion validator:
+// wherever the curve becomes invalid the polyline is refined with extra vertices there,
+// which pulls the curve toward the original path, and the spline is fitted again.
+pub struct SplineSmoother<'a, MV: 'a> {
+    validator: &'a MV,
+    kind: SplineKind,
+    samples_per_segment: usize,
+    max_iterations: usize,
+}
+
+impl<'a, MV> SplineSmoother<'a, MV> where MV: MotionValidator<Vec<f64>> {
+    pub fn new(validator: &'a MV, kind: SplineKind) -> SplineSmoother<'a, MV> {
+        SplineSmoother { validator, kind, samples_per_segment: 16, max_iterations: 8, }
+    }
+
+    pub fn samples_per_segment(mut self, samples_per_segment: usize) -> SplineSmoother<'a, MV> {
+        assert!(samples_per_segment > 0, "at least one sample per segment required");
+        self.samples_per_segment = samples_per_segment;
+        self
+    }
+
+    pub fn max_iterations(mut self, max_iterations: usize) -> SplineSmoother<'a, MV> {
+        self.max_iterations = max_iterations;
+        self
+    }
+
+    pub fn smooth(&self, path: &[Vec<f64>]) -> SmoothedPath {
+        // nothing to smooth with less than three points
+        if path.len() < 3 {
+            return SmoothedPath { states: path.to_vec(), max_curvature: 0.0, iterations: 0, smoothed: true, };
+        }
+        let mut polyline = path.to_vec();
+        for iteration in 1 ..= self.max_iterations {
+            let spline = Spline { kind: self.kind, points: &polyline, };
+            let (states, max_curvature, invalid) = self.sample(&spline);
+            if invalid.is_empty() {
+                return SmoothedPath { states, max_curvature, iterations: iteration, smoothed: true, };
+            }
+            let mut refine = vec![false; polyline.len() - 1];
+            for segment in invalid {
+                let (lo, hi) = spline.support(segment);
+                for flag in refine[lo .. hi].iter_mut() {
+                    *flag = true;
+                }
+            }
+            polyline = refine_polyline(&polyline, &refine);
+        }
+        SmoothedPath {
+            max_curvature: polyline_curvature(path),
+            states: path.to_vec(),
+            iterations: self.max_iterations,
+            smoothed: false,
+        }
+    }
+
+    fn sample(&self, spline: &Spline) -> (Vec<Vec<f64>>, f64, Vec<usize>) {
+        let mut states = vec![spline.points[0].clone()];
+        let mut max_curvature: f64 = 0.0;
+        let mut invalid = Vec::new();
+        for segment in 0 .. spline.segments() {
+            let mut segment_valid = true;
+            for step in 1 ..= self.samples_per_segment {
+                let t = step as f64 / self.samples_per_segment as f64;
+                let (state, d1, d2) = spline.eval(segment, t);
+                max_curvature = max_curvature.max(curvature(&d1, &d2));
+                if segment_valid && !self.validator.is_motion_valid(states.last().unwrap(), &state) {
+                    segment_valid = false;
+                    invalid.push(segment);
+                }
+                states.push(state);
+            }
+        }
+        (states, max_curvature, invalid)
+    }
+}
+
+fn refine_polyline(polyline: &[Vec<f64>], refine: &[bool]) -> Vec<Vec<f64>> {
+    let mut refined = vec![polyline[0].clone()];
+    for (pair, &split) in polyline.windows(2).zip(refine.iter()) {
+        if split {
+            let (a, b) = (&pair[0], &pair[1]);
+            refined.push(a.iter().zip(b.iter()).map(|(a, b)| a + (b - a) * 0.25).collect());
+            refined.push(a.iter().zip(b.iter()).map(|(a, b)| a + (b - a) * 0.75).collect());
+        }
+        refined.push(pair[1].clone());
+    }
+    refined
+}
+
+fn dot(a: &[f64], b: &[f64]) -> f64 {
+    a.iter().zip(b.iter()).map(|(a, b)| a * b).sum()
+}
+
+// curvature of a parametric curve in any dimension from its first and second derivatives
+pub fn curvature(d1: &[f64], d2: &[f64]) -> f64 {
+    let speed_sq = dot(d1, d1);
+    if speed_sq <= 1e-18 {
+        return 0.0;
+    }
+    let numerator = (speed_sq * dot(d2, d2) - dot(d1, d2).powi(2)).max(0.0).sqrt();
+    numerator / speed_sq.powf(1.5)
+}
+
+fn polyline_curvature(path: &[Vec<f64>]) -> f64 {
+    let has_corner = path.windows(3).any(|triple| {
+        let u: Vec<_> = triple[1].iter().zip(triple[0].iter()).map(|(b, a)| b - a).collect();
+        let v: Vec<_> = triple[2].iter().zip(triple[1].iter()).map(|(c, b)| c - b).collect();
+        let (uu, vv, uv) = (dot(&u, &u), dot(&v, &v), dot(&u, &v));
+        if uu == 0.0 || vv == 0.0 {
+            // repeated vertex, no direction change
+            return false;
+        }
+        // doubling back is the sharpest corner of all, otherwise compare `sin^2` of the turn
+        // angle so the threshold does not depend on segment lengths
+        uv < 0.0 || uu * vv - uv * uv > 1e-12 * uu * vv
+    });
+    if has_corner { f64::INFINITY } else { 0.0 }
+}
+
+// Spline
+
+struct Spline<'p> {
+    kind: SplineKind,
+    points: &'p [Vec<f64>],
+}
+
+impl<'p> Spline<'p> {
+    fn segments(&self) -> usize {
+        match self.kind {
+            // control polygon with endpoints tripled
+            SplineKind::BSpline => self.points.len() + 1,
+            SplineKind::Hermite { .. } => self.points.len() - 1,
+        }
+    }
+
+    // range of polyline segments controlling the spline segment
+    fn support(&self, segment: usize) -> (usize, usize) {
+        let last = self.points.len() - 1;
+        match self.kind {
+            SplineKind::BSpline => (segment.saturating_sub(2), (segment + 1).min(last)),
+            SplineKind::Hermite { .. } => (segment.saturating_sub(1), (segment + 2).min(last)),
+        }
+    }
+
+    fn point(&self, index: isize) -> &[f64] {
+        let clamped = index.max(0).min(self.points.len() as isize - 1);
+        &self.points[clamped as usize]
+    }
+
+    // position, first and second derivatives at `t` within the segment
+    fn eval(&self, segment: usize, t: f64) -> (Vec<f64>, Vec<f64>, Vec<f64>) {
+        let (ctrl, w, w1, w2) = match self.kind {
+            SplineKind::BSpline => {
+                let base = segment as isize - 2;
+                let ctrl = [self.point(base), self.point(base + 1), self.point(base + 2), self.point(base + 3)];
+                let s = 1.0 - t;
+                let w = [
+                    s * s * s / 6.0,
+                    (3.0 * t * t * t - 6.0 * t * t + 4.0) / 6.0,
+                    (-3.0 * t * t * t + 3.0 * t * t + 3.0 * t + 1.0) / 6.0,
+                    t * t * t / 6.0,
+                ];
+                let w1 = [-s * s / 2.0, (3.0 * t * t - 4.0 * t) / 2.0, (-3.0 * t * t + 2.0 * t + 1.0) / 2.0, t * t / 2.0];
+                let w2 = [s, 3.0 * t - 2.0, -3.0 * t + 1.0, t];
+                (ctrl, w, w1, w2)
+            },
+            SplineKind::Hermite { tension, } => {
+                // expressed over neighbour points: tangent `m_i = tension * (p_{i+1} - p_{i-1}) / 2`
+                // with one-sided differences at the endpoints
+                let i = segment as isize;
+                let last = self.points.len() as isize - 1;
+                let (k0, k1) = (if i == 0 { 1.0 } else { 0.5 }, if i + 1 == last { 1.0 } else { 0.5 });
+                let ctrl = [self.point(i - 1), self.point(i), self.point(i + 1), self.point(i + 2)];
+                let h = |h00: f64, h10: f64, h01: f64, h11: f64| {
+                    let (m0, m1) = (tension * k0 * h10, tension * k1 * h11);
+                    [-m0, h00 - m1, h01 + m0, m1]
+                };
+                let t2 = t * t;
+                let t3 = t2 * t;
+                let w = h(2.0 * t3 - 3.0 * t2 + 1.0, t3 - 2.0 * t2 + t, -2.0 * t3 + 3.0 * t2, t3 - t2);
+                let w1 = h(6.0 * t2 - 6.0 * t, 3.0 * t2 - 4.0 * t + 1.0, -6.0 * t2 + 6.0 * t, 3.0 * t2 - 2.0 * t);
+                let w2 = h(12.0 * t - 6.0, 6.0 * t - 4.0, -12.0 * t + 6.0, 6.0 * t - 2.0);
+                (ctrl, w, w1, w2)
+            },
+        };
+        let combine = |weights: [f64; 4]| -> Vec<f64> {
+            (0 .. ctrl[0].len())
+                .map(|axis| (0 .. 4).map(|k| weights[k] * ctrl[k][axis]).sum())
+                .collect()
+        };
+        (combine(w), combine(w1), combine(w2))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use util::geom2d::{ObstacleWorld, Aabb, Shape};
+
+    #[test]
+    fn smooth_around_corner() {
+        let mut world = ObstacleWorld::new(Aabb::new([0.0, 0.0], [10.0, 10.0]), 2.0);
+        world.add_obstacle(Shape::Box(Aabb::new([2.1, 0.0], [10.0, 7.9])));
+        let path = vec![vec![1.0, 1.0], vec![1.0, 5.0], vec![2.0, 8.0], vec![9.0, 9.0]];
+        assert!(path.windows(2).all(|pair| world.is_motion_valid(&pair[0], &pair[1])));
+
+        for &kind in &[SplineKind::BSpline, SplineKind::Hermite { tension: 1.0, }] {
+            let smoothed = SplineSmoother::new(&world, kind).smooth(&path);
+            assert!(smoothed.smoothed);
+            assert!(smoothed.max_curvature.is_finite());
+            assert_eq!(smoothed.states.first(), path.first());
+            let last = smoothed.states.last().unwrap();
+            assert!((last[0] - 9.0).abs() < 1e-9 && (last[1] - 9.0).abs() < 1e-9);
+            assert!(smoothed.states.windows(2).all(|pair| world.is_motion_valid(&pair[0], &pair[1])));
+        }
+    }
+
+    #[test]
+    fn short_paths() {
+        let world = ObstacleWorld::new(Aabb::new([0.0, 0.0], [10.0, 10.0]), 2.0);
+        let smoother = SplineSmoother::new(&world, SplineKind::BSpline);
+        for path in [vec![], vec![vec![1.0, 1.0]], vec![vec![1.0, 1.0], vec![5.0, 2.0]]] {
+            let smoothed = smoother.smooth(&path);
+            assert_eq!(smoothed, SmoothedPath { states: path, max_curvature: 0.0, iterations: 0, smoothed: true, });
+        }
+    }
+
+    #[test]
+    fn straight_line_curvature() {
+        assert_eq!(curvature(&[1.0, 1.0], &[2.0, 2.0]), 0.0);
+        // circle of radius 2: r' = (-2 sin, 2 cos), r'' = (-2 cos, -2 sin) at angle 0
+        assert!((curvature(&[0.0, 2.0], &[-2.0, 0.0]) - 0.5).abs() < 1e-12);
+    }
+
+    #[test]
+    fn polyline_corners() {
+        assert_eq!(polyline_curvature(&[vec![0.0, 0.0], vec![1.0, 0.0], vec![3.0, 0.0]]), 0.0);
+        assert_eq!(polyline_curvature(&[vec![0.0, 0.0], vec![1.0, 0.0], vec![0.0, 0.0]]), f64::INFINITY);
+        assert_eq!(polyline_curvature(&[vec![0.0, 0.0], vec![1.0, 0.0], vec![1.0, 0.0], vec![2.0, 0.0]]), 0.0);
+        // the same small turn at very different scales
+        assert_eq!(polyline_curvature(&[vec![0.0, 0.0], vec![1e-4, 0.0], vec![2e-4, 1e-8]]), f64::INFINITY);
+        assert_eq!(polyline_curvature(&[vec![0.0, 0.0], vec![1e4, 0.0], vec![2e4, 1.0]]), f64::INFINITY);
+        assert_eq!(polyline_curvature(&[vec![0.0, 0.0], vec![1e4, 0.0], vec![2e4, 1e-9]]), 0.0);
+    }
+}
@@ -0,0 +1,271 @@
+use util::space::euclidean_distance;
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Limits {
+    pub velocity: Vec<f64>,
+    pub acceleration: Vec<f64>,
+}
+
+impl Limits {
+    pub fn new(velocity: Vec<f64>, acceleration: Vec<f64>) -> Limits {
+        assert_eq!(velocity.len(), acceleration.len(), "velocity and acceleration limits dimensions mismatch");
+        assert!(velocity.iter().chain(acceleration.iter()).all(|&limit| limit > 0.0), "limits should be positive");
+        Limits { velocity, acceleration, }
+    }
+
+    // limits along a unit direction so that no single dimension exceeds its own bound
+    fn along(&self, direction: &[f64]) -> (f64, f64) {
+        direction.iter()
+            .enumerate()
+            .filter(|&(_, component)| component.abs() > 1e-12)
+            .fold((f64::INFINITY, f64::INFINITY), |(v, a), (axis, component)| {
+                (v.min(self.velocity[axis] / component.abs()), a.min(self.acceleration[axis] / component.abs()))
+            })
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum JunctionModel {
+    // come to a full stop at every vertex where the path turns: exact path following
+    Stop,
+    // treat turns as samples of a smooth curve and bound the centripetal acceleration
+    // `v^2 * |du_i| / l` per dimension (`l` is the mean length of adjacent segments);
+    // suits densely sampled smooth paths such as spline output
+    Blended,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct TrajectoryPoint {
+    pub time: f64,
+    pub distance: f64,
+    pub speed: f64,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct TrajectoryState {
+    pub time: f64,
+    pub position: Vec<f64>,
+    pub velocity: Vec<f64>,
+    pub acceleration: Vec<f64>,
+}
+
+// trapezoidal or triangular speed profile over a straight segment
+#[derive(Clone, Debug)]
+struct Segment {
+    start_time: f64,
+    from: Vec<f64>,
+    direction: Vec<f64>,
+    length: f64,
+    entry_speed: f64,
+    peak_speed: f64,
+    exit_speed: f64,
+    acceleration: f64,
+    accel_time: f64,
+    cruise_time: f64,
+    decel_time: f64,
+}
+
+impl Segment {
+    fn duration(&self) -> f64 {
+        self.accel_time + self.cruise_time + self.decel_time
+    }
+
+    // distance, speed and signed path acceleration at local time `t`
+    fn profile(&self, t: f64) -> (f64, f64, f64) {
+        let a = self.acceleration;
+        if t < self.accel_time {
+            (self.entry_speed * t + a * t * t / 2.0, self.entry_speed + a * t, a)
+        } else if t < self.accel_time + self.cruise_time {
+            let accel_distance = (self.peak_speed + self.entry_speed) / 2.0 * self.accel_time;
+            (accel_distance + self.peak_speed * (t - self.accel_time), self.peak_speed, 0.0)
+        } else {
+            let t = (t - self.accel_time - self.cruise_time).min(self.decel_time);
+            let before = (self.peak_speed + self.entry_speed) / 2.0 * self.accel_time + self.peak_speed * self.cruise_time;
+            (before + self.peak_speed * t - a * t * t / 2.0, self.peak_speed - a * t, -a)
+        }
+    }
+}
+
+// Trajectory
+
+// Time parameterization of a piecewise linear path: with `JunctionModel::Stop` the result
+// is time-optimal for following the path exactly under per-dimension limits.
+#[derive(Clone, Debug)]
+pub struct Trajectory {
+    segments: Vec<Segment>,
+    end: Vec<f64>,
+    duration: f64,
+}
+
+impl Trajectory {
+    pub fn new(path: &[Vec<f64>], limits: &Limits, junction: JunctionModel) -> Trajectory {
+        assert!(!path.is_empty(), "cannot parameterize an empty path");
+        let mut points: Vec<&Vec<f64>> = vec![&path[0]];
+        for state in path.iter().skip(1) {
+            if euclidean_distance(points.last().unwrap(), state) > 1e-12 {
+                points.push(state);
+            }
+        }
+        let lengths: Vec<f64> = points.windows(2).map(|pair| euclidean_distance(pair[0], pair[1])).collect();
+        let directions: Vec<Vec<f64>> = points.windows(2)
+            .zip(lengths.iter())
+            .map(|(pair, length)| pair[1].iter().zip(pair[0].iter()).map(|(b, a)| (b - a) / length).collect())
+            .collect();
+        let bounds: Vec<(f64, f64)> = directions.iter().map(|direction| limits.along(direction)).collect();
+
+        // vertex speed limits
+        let count = points.len();
+        let mut speeds = vec![0.0; count];
+        for (vertex, speed) in speeds.iter_mut().enumerate().take(count - 1).skip(1) {
+            let (prev, next) = (vertex - 1, vertex);
+            let cap = bounds[prev].0.min(bounds[next].0);
+            let turn: Vec<f64> = directions[next].iter().zip(directions[prev].iter()).map(|(b, a)| b - a).collect();
+            *speed = if turn.iter().all(|du| du.abs() < 1e-9) {
+                cap
+            } else {
+                match junction {
+                    JunctionModel::Stop =>
+                        0.0,
+                    JunctionModel::Blended => {
+                        let mean_length = (lengths[prev] + lengths[next]) / 2.0;
+                        turn.iter()
+                            .enumerate()
+                            .filter(|&(_, du)| du.abs() > 1e-12)
+                            .map(|(axis, du)| (limits.acceleration[axis] * mean_length / du.abs()).sqrt())
+                            .fold(cap, f64::min)
+                    },
+                }
+            };
+        }
+        // forward and backward passes over reachable speeds
+        for k in 0 .. count - 1 {
+            let reachable = (speeds[k] * speeds[k] + 2.0 * bounds[k].1 * lengths[k]).sqrt();
+            speeds[k + 1] = speeds[k + 1].min(reachable);
+        }
+        for k in (0 .. count - 1).rev() {
+            let reachable = (speeds[k + 1] * speeds[k + 1] + 2.0 * bounds[k].1 * lengths[k]).sqrt();
+            speeds[k] = speeds[k].min(reachable);
+        }
+
+        let mut segments = Vec::with_capacity(count - 1);
+        let mut time = 0.0;
+        for k in 0 .. count - 1 {
+            let (max_speed, acceleration) = bounds[k];
+            let (entry_speed, exit_speed, length) = (speeds[k], speeds[k + 1], lengths[k]);
+            let peak_speed = ((2.0 * acceleration * length + entry_speed * entry_speed + exit_speed * exit_speed) / 2.0)
+                .sqrt()
+                .min(max_speed)
+                .max(entry_speed.max(exit_speed));
+            let accel_distance = (peak_speed * peak_speed - entry_speed * entry_speed) / (2.0 * acceleration);
+            let decel_distance = (peak_speed * peak_speed - exit_speed * exit_speed) / (2.0 * acceleration);
+            let segment = Segment {
+                start_time: time,
+                from: points[k].clone(),
+                direction: directions[k].clone(),
+                length,
+                entry_speed,
+                peak_speed,
+                exit_speed,
+                acceleration,
+                accel_time: (peak_speed - entry_speed) / acceleration,
+                cruise_time: ((length - accel_distance - decel_distance) / peak_speed).max(0.0),
+                decel_time: (peak_speed - exit_speed) / acceleration,
+            };
+            time += segment.duration();
+            segments.push(segment);
+        }
+        Trajectory { segments, end: points[count - 1].clone(), duration: time, }
+    }
+
+    pub fn duration(&self) -> f64 {
+        self.duration
+    }
+
+    // arrival time, travelled distance and speed at each path vertex (duplicates removed)
+    pub fn waypoints(&self) -> Vec<TrajectoryPoint> {
+        let mut distance = 0.0;
+        let mut points: Vec<_> = self.segments.iter()
+            .map(|segment| {
+                let point = TrajectoryPoint { time: segment.start_time, distance, speed: segment.entry_speed, };
+                distance += segment.length;
+                point
+            })
+            .collect();
+        points.push(TrajectoryPoint {
+            time: self.duration,
+            distance,
+            speed: self.segments.last().map(|segment| segment.exit_speed).unwrap_or(0.0),
+        });
+        points
+    }
+
+    // state at an arbitrary time, clamped to `[0, duration]`
+    pub fn sample(&self, time: f64) -> TrajectoryState {
+        let time = time.clamp(0.0, self.duration);
+        let index = self.segments.iter()
+            .rposition(|segment| segment.start_time <= time);
+        match index {
+            None => TrajectoryState {
+                time,
+                position: self.end.clone(),
+                velocity: vec![0.0; self.end.len()],
+                acceleration: vec![0.0; self.end.len()],
+            },
+            Some(index) => {
+                let segment = &self.segments[index];
+                let (distance, speed, acceleration) = segment.profile(time - segment.start_time);
+                let distance = distance.min(segment.length);
+                TrajectoryState {
+                    time,
+                    position: segment.from.iter().zip(segment.direction.iter()).map(|(p, u)| p + u * distance).collect(),
+                    velocity: segment.direction.iter().map(|u| u * speed).collect(),
+                    acceleration: segment.direction.iter().map(|u| u * acceleration).collect(),
+                }
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-9
+    }
+
+    #[test]
+    fn trapezoid() {
+        let limits = Limits::new(vec![2.0], vec![1.0]);
+        let trajectory = Trajectory::new(&[vec![0.0], vec![4.0], vec![10.0]], &limits, JunctionModel::Stop);
+        assert!(close(trajectory.duration(), 7.0));
+        let state = trajectory.sample(1.0);
+        assert!(close(state.position[0], 0.5) && close(state.velocity[0], 1.0) && close(state.acceleration[0], 1.0));
+        let state = trajectory.sample(3.5);
+        assert!(close(state.position[0], 5.0) && close(state.velocity[0], 2.0));
+        let state = trajectory.sample(6.5);
+        assert!(close(state.position[0], 9.875) && close(state.velocity[0], 0.5));
+        assert_eq!(trajectory.sample(100.0).position, vec![10.0]);
+    }
+
+    #[test]
+    fn per_dimension_limits_and_corners() {
+        let limits = Limits::new(vec![1.0, 0.5], vec![1.0, 1.0]);
+        let path = [vec![0.0, 0.0], vec![4.0, 0.0], vec![4.0, 4.0]];
+        let trajectory = Trajectory::new(&path, &limits, JunctionModel::Stop);
+        let waypoints = trajectory.waypoints();
+        assert_eq!(waypoints.len(), 3);
+        assert!(close(waypoints[1].speed, 0.0));
+        // x: 1s accel, 3s cruise, 1s decel; y: 0.5s accel, 7.5s cruise, 0.5s decel
+        assert!(close(waypoints[1].time, 5.0));
+        assert!(close(trajectory.duration(), 13.5));
+        for step in 0 .. 135 {
+            let state = trajectory.sample(step as f64 / 10.0);
+            assert!(state.velocity[0].abs() <= 1.0 + 1e-9 && state.velocity[1].abs() <= 0.5 + 1e-9);
+        }
+
+        let blended = Trajectory::new(&path, &limits, JunctionModel::Blended);
+        assert!(blended.waypoints()[1].speed > 0.0);
+        assert!(blended.duration() < trajectory.duration());
+    }
+}
@@ -0,0 +1,343 @@
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use std::f64::consts::E;
+
+use util::path::Path;
+use util::nearest::NearestNeighbours;
+use util::sample::Sampler;
+use util::space::Metric;
+use util::validity::{StateValidityChecker, MotionValidator};
+
+// Probabilistic roadmap: an undirected graph of valid states grown once and then
+// reused for many queries in the same static environment.
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub struct VertexRef(usize);
+
+#[derive(Clone, Debug)]
+pub struct Roadmap<S> {
+    states: Vec<S>,
+    edges: Vec<Vec<(usize, f64)>>,
+}
+
+impl<S> Roadmap<S> {
+    pub fn new() -> Roadmap<S> {
+        Roadmap { states: Vec::new(), edges: Vec::new(), }
+    }
+
+    pub fn vertices_count(&self) -> usize {
+        self.states.len()
+    }
+
+    pub fn edges_count(&self) -> usize {
+        self.edges.iter().map(Vec::len).sum::<usize>() / 2
+    }
+
+    pub fn add_vertex(&mut self, state: S) -> VertexRef {
+        self.states.push(state);
+        self.edges.push(Vec::new());
+        VertexRef(self.states.len() - 1)
+    }
+
+    pub fn add_edge(&mut self, VertexRef(a): VertexRef, VertexRef(b): VertexRef, cost: f64) {
+        self.edges[a].push((b, cost));
+        self.edges[b].push((a, cost));
+    }
+
+    pub fn get_state(&self, &VertexRef(vertex): &VertexRef) -> &S {
+        &self.states[vertex]
+    }
+
+    pub fn neighbours<'a>(&'a self, &VertexRef(vertex): &VertexRef) -> impl Iterator<Item = (VertexRef, f64)> + 'a {
+        self.edges[vertex].iter().map(|&(neighbour, cost)| (VertexRef(neighbour), cost))
+    }
+
+    pub fn states(&self) -> impl Iterator<Item = (VertexRef, &S)> + '_ {
+        self.states.iter().enumerate().map(|(vertex, state)| (VertexRef(vertex), state))
+    }
+
+    // drops every vertex from `count` on together with the edges leading to them
+    fn truncate(&mut self, count: usize) {
+        let (kept, dropped) = self.edges.split_at_mut(count);
+        for &(neighbour, _) in dropped.iter().flatten() {
+            if neighbour < count {
+                kept[neighbour].retain(|&(other, _)| other < count);
+            }
+        }
+        self.states.truncate(count);
+        self.edges.truncate(count);
+    }
+}
+
+impl<S> NearestNeighbours<S> for Roadmap<S> {
+    type Ref = VertexRef;
+
+    fn visit_states<F>(&self, mut visit: F) where F: FnMut(VertexRef, &S) {
+        for (vertex, state) in self.states.iter().enumerate() {
+            visit(VertexRef(vertex), state);
+        }
+    }
+}
+
+impl<S> Default for Roadmap<S> {
+    fn default() -> Roadmap<S> {
+        Roadmap::new()
+    }
+}
+
+// Connection
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Connection {
+    Radius(f64),
+    KNearest(usize),
+    // PRM*: radius `gamma * (ln n / n) ^ (1 / dimension)` shrinking with the roadmap size
+    RadiusStar { gamma: f64, dimension: usize },
+    // PRM*: `e * (1 + 1 / dimension) * ln n` nearest neighbours
+    KNearestStar { dimension: usize },
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum Neighbourhood {
+    Radius(f64),
+    KNearest(usize),
+}
+
+impl Connection {
+    fn neighbourhood(&self, vertices_count: usize) -> Neighbourhood {
+        let n = vertices_count.max(2) as f64;
+        match *self {
+            Connection::Radius(radius) =>
+                Neighbourhood::Radius(radius),
+            Connection::KNearest(k) =>
+                Neighbourhood::KNearest(k),
+            Connection::RadiusStar { gamma, dimension, } =>
+                Neighbourhood::Radius(gamma * (n.ln() / n).powf(1.0 / dimension as f64)),
+            Connection::KNearestStar { dimension, } =>
+                Neighbourhood::KNearest((E * (1.0 + 1.0 / dimension as f64) * n.ln()).ceil() as usize),
+        }
+    }
+}
+
+// Prm
+
+pub struct Prm<'a, VC: 'a, MV: 'a, M> {
+    checker: &'a VC,
+    validator: &'a MV,
+    metric: M,
+    connection: Connection,
+}
+
+impl<'a, VC, MV, M> Prm<'a, VC, MV, M> {
+    pub fn new(checker: &'a VC, validator: &'a MV, metric: M, connection: Connection) -> Prm<'a, VC, MV, M> {
+        Prm { checker, validator, metric, connection, }
+    }
+
+    // Draws `samples` states, keeps the valid ones and connects each of them to its
+    // neighbourhood with valid motions. Returns the number of vertices added.
+    pub fn grow<S, SM>(&self, roadmap: &mut Roadmap<S>, sampler: &mut SM, samples: usize) -> usize
+        where SM: Sampler<Sample = S>,
+              VC: StateValidityChecker<S>,
+              MV: MotionValidator<S>,
+              M: Metric<S>,
+    {
+        let mut added = 0;
+        for _ in 0 .. samples {
+            let state = sampler.sample();
+            if self.checker.is_valid(&state) {
+                self.connect(roadmap, state);
+                added += 1;
+            }
+        }
+        added
+    }
+
+    fn connect<S>(&self, roadmap: &mut Roadmap<S>, state: S) -> VertexRef
+        where MV: MotionValidator<S>,
+              M: Metric<S>,
+    {
+        let distance = |other: &S| self.metric.distance(&state, other);
+        let near = match self.connection.neighbourhood(roadmap.vertices_count() + 1) {
+            Neighbourhood::Radius(radius) =>
+                roadmap.within(radius, distance),
+            Neighbourhood::KNearest(k) =>
+                roadmap.k_nearest(k, distance),
+        };
+        let valid: Vec<_> = near.into_iter()
+            .filter(|&(vertex_ref, _)| self.validator.is_motion_valid(&state, roadmap.get_state(&vertex_ref)))
+            .collect();
+        let vertex_ref = roadmap.add_vertex(state);
+        for (vertex, distance) in valid {
+            roadmap.add_edge(vertex_ref, vertex, distance);
+        }
+        vertex_ref
+    }
+
+    // Connects `start` and `goal` to the roadmap for the duration of the query and
+    // runs A* guided by the metric distance to the goal. The roadmap is left as it was.
+    pub fn query<S>(&self, roadmap: &mut Roadmap<S>, start: S, goal: S) -> Option<Path<S>>
+        where S: Clone,
+              VC: StateValidityChecker<S>,
+              MV: MotionValidator<S>,
+              M: Metric<S>,
+    {
+        if !self.checker.is_valid(&start) || !self.checker.is_valid(&goal) {
+            return None;
+        }
+        let count = roadmap.vertices_count();
+        let start_ref = self.connect(roadmap, start);
+        let goal_ref = self.connect(roadmap, goal);
+        // the goal may already be linked to the start as one of its neighbours
+        let linked = roadmap.neighbours(&goal_ref).any(|(vertex_ref, _)| vertex_ref == start_ref);
+        if !linked && self.validator.is_motion_valid(roadmap.get_state(&start_ref), roadmap.get_state(&goal_ref)) {
+            let distance = self.metric.distance(roadmap.get_state(&start_ref), roadmap.get_state(&goal_ref));
+            roadmap.add_edge(start_ref, goal_ref, distance);
+        }
+        let path = self.astar(roadmap, start_ref, goal_ref);
+        roadmap.truncate(count);
+        path
+    }
+
+    pub fn astar<S>(&self, roadmap: &Roadmap<S>, VertexRef(start): VertexRef, VertexRef(goal): VertexRef) -> Option<Path<S>>
+        where S: Clone,
+              M: Metric<S>,
+    {
+        let goal_state = &roadmap.states[goal];
+        let mut cost = vec![f64::INFINITY; roadmap.vertices_count()];
+        let mut prev: Vec<Option<usize>> = vec![None; roadmap.vertices_count()];
+        let mut queue = BinaryHeap::new();
+        cost[start] = 0.0;
+        queue.push(Frontier { estimate: self.metric.distance(&roadmap.states[start], goal_state), vertex: start, });
+        while let Some(Frontier { vertex, .. }) = queue.pop() {
+            if vertex == goal {
+                let mut rev_states = vec![roadmap.states[goal].clone()];
+                let mut current = goal;
+                while let Some(previous) = prev[current] {
+                    rev_states.push(roadmap.states[previous].clone());
+                    current = previous;
+                }
+                return Some(Path::from_rev_states(rev_states));
+            }
+            for &(neighbour, edge_cost) in &roadmap.edges[vertex] {
+                let next_cost = cost[vertex] + edge_cost;
+                if next_cost < cost[neighbour] {
+                    cost[neighbour] = next_cost;
+                    prev[neighbour] = Some(vertex);
+                    let estimate = next_cost + self.metric.distance(&roadmap.states[neighbour], goal_state);
+                    queue.push(Frontier { estimate, vertex: neighbour, });
+                }
+            }
+        }
+        None
+    }
+}
+
+// min-heap entry ordered by the A* estimate
+struct Frontier {
+    estimate: f64,
+    vertex: usize,
+}
+
+impl PartialEq for Frontier {
+    fn eq(&self, other: &Frontier) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Frontier {}
+
+impl PartialOrd for Frontier {
+    fn partial_cmp(&self, other: &Frontier) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Frontier {
+    fn cmp(&self, other: &Frontier) -> Ordering {
+        other.estimate.total_cmp(&self.estimate)
+            .then_with(|| other.vertex.cmp(&self.vertex))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Roadmap, Prm, Connection, Neighbourhood};
+    use util::geom2d::{ObstacleWorld, Aabb, Shape};
+    use util::sample::quasi::{Halton, QuasiRandomSampler};
+    use util::space::{Bounds, Euclidean};
+
+    fn world() -> ObstacleWorld {
+        let mut world = ObstacleWorld::new(Aabb::new([0.0, 0.0], [1.0, 1.0]), 0.1);
+        world.add_obstacle(Shape::Box(Aabb::new([0.4, 0.0], [0.6, 0.8])));
+        world
+    }
+
+    #[test]
+    fn many_queries() {
+        let world = world();
+        for &connection in &[Connection::Radius(0.2), Connection::KNearest(8), Connection::KNearestStar { dimension: 2, }] {
+            let prm = Prm::new(&world, &world, Euclidean, connection);
+            let mut sampler = QuasiRandomSampler::new(Halton::with_dimension(2), Bounds::new(vec![0.0, 0.0], vec![1.0, 1.0]));
+            let mut roadmap = Roadmap::new();
+            let added = prm.grow(&mut roadmap, &mut sampler, 300);
+            assert_eq!(roadmap.vertices_count(), added);
+            let edges = roadmap.edges_count();
+
+            let path = prm.query(&mut roadmap, vec![0.1, 0.1], vec![0.9, 0.1]).unwrap();
+            assert_eq!(path.first(), Some(&vec![0.1, 0.1]));
+            assert_eq!(path.last(), Some(&vec![0.9, 0.1]));
+            assert!(path.is_valid(&world));
+            assert!(path.states().iter().any(|state| state[1] > 0.8));
+            assert!(path.cost(&Euclidean) >= 2.0 * (0.3_f64.powi(2) + 0.7_f64.powi(2)).sqrt());
+
+            let direct = prm.query(&mut roadmap, vec![0.1, 0.1], vec![0.2, 0.3]).unwrap();
+            assert_eq!(direct.len(), 2);
+            assert!(prm.query(&mut roadmap, vec![0.5, 0.5], vec![0.9, 0.1]).is_none());
+
+            assert_eq!(roadmap.vertices_count(), added);
+            assert_eq!(roadmap.edges_count(), edges);
+        }
+    }
+
+    #[test]
+    fn radius_star_shrinks() {
+        let connection = Connection::RadiusStar { gamma: 1.5, dimension: 2, };
+        let radius = |vertices_count| match connection.neighbourhood(vertices_count) {
+            Neighbourhood::Radius(radius) => radius,
+            Neighbourhood::KNearest(..) => unreachable!(),
+        };
+        assert!(radius(10) > radius(100) && radius(100) > radius(1000));
+
+        let world = world();
+        let prm = Prm::new(&world, &world, Euclidean, connection);
+        let mut sampler = QuasiRandomSampler::new(Halton::with_dimension(2), Bounds::new(vec![0.0, 0.0], vec![1.0, 1.0]));
+        let mut roadmap = Roadmap::new();
+        prm.grow(&mut roadmap, &mut sampler, 500);
+        // every edge was made when its later vertex joined the roadmap, within the radius of that time
+        for (vertex_ref, _) in roadmap.states() {
+            for (neighbour, cost) in roadmap.neighbours(&vertex_ref) {
+                assert!(cost <= radius(vertex_ref.0.max(neighbour.0) + 1));
+            }
+        }
+        // while early edges are longer than the final radius would allow
+        let final_radius = radius(roadmap.vertices_count());
+        assert!(roadmap.states().flat_map(|(vertex_ref, _)| roadmap.neighbours(&vertex_ref)).any(|(_, cost)| cost > final_radius));
+
+        let path = prm.query(&mut roadmap, vec![0.1, 0.1], vec![0.9, 0.1]).unwrap();
+        assert!(path.is_valid(&world));
+    }
+
+    #[test]
+    fn single_start_goal_edge() {
+        let world = world();
+        let prm = Prm::new(&world, &world, Euclidean, Connection::Radius(0.3));
+        let mut roadmap = Roadmap::new();
+        let path = prm.query(&mut roadmap, vec![0.1, 0.1], vec![0.2, 0.2]).unwrap();
+        assert_eq!(path.len(), 2);
+
+        let start_ref = prm.connect(&mut roadmap, vec![0.1, 0.1]);
+        let goal_ref = prm.connect(&mut roadmap, vec![0.2, 0.2]);
+        assert_eq!(roadmap.neighbours(&goal_ref).filter(|&(vertex_ref, _)| vertex_ref == start_ref).count(), 1);
+        assert_eq!(roadmap.edges_count(), 1);
+    }
+}
@@ -0,0 +1,204 @@
+use std::marker::PhantomData;
+
+use util::path::Path;
+use util::nearest::NearestNeighbours;
+
+// Several single-linked trees in one `Vec`, each node remembers the root it grows from.
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub struct NodeRef(usize);
+
+// Roots are numbered in the order they were added.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub struct RootIndex(pub usize);
+
+#[derive(Clone)]
+struct ForestNode<S> {
+    state: S,
+    prev: Option<usize>,
+    root: RootIndex,
+}
+
+pub struct EmptyRandomForest<S> {
+    _marker: PhantomData<S>,
+}
+
+impl<S> EmptyRandomForest<S> {
+    pub fn new() -> EmptyRandomForest<S> {
+        EmptyRandomForest {
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn add_root(self, state: S) -> RandomForest<S> {
+        let mut forest = RandomForest { nodes: Vec::new(), roots: Vec::new(), };
+        forest.add_root(state);
+        forest
+    }
+
+    // `None` if there are no roots at all
+    pub fn add_roots<I>(self, states: I) -> Option<RandomForest<S>> where I: IntoIterator<Item = S> {
+        let mut states = states.into_iter();
+        let mut forest = self.add_root(states.next()?);
+        for state in states {
+            forest.add_root(state);
+        }
+        Some(forest)
+    }
+}
+
+impl<S> Clone for EmptyRandomForest<S> {
+    fn clone(&self) -> EmptyRandomForest<S> {
+        EmptyRandomForest::new()
+    }
+}
+
+impl<S> Default for EmptyRandomForest<S> {
+    fn default() -> EmptyRandomForest<S> {
+        EmptyRandomForest::new()
+    }
+}
+
+pub struct RootedPath<S> {
+    pub root: RootIndex,
+    pub path: Path<S>,
+}
+
+#[derive(Clone)]
+pub struct RandomForest<S> {
+    nodes: Vec<ForestNode<S>>,
+    roots: Vec<usize>,
+}
+
+impl<S> RandomForest<S> {
+    pub fn add_root(&mut self, state: S) -> NodeRef {
+        let node_index = self.nodes.len();
+        let root = RootIndex(self.roots.len());
+        self.nodes.push(ForestNode { state, prev: None, root, });
+        self.roots.push(node_index);
+        NodeRef(node_index)
+    }
+
+    pub fn roots_count(&self) -> usize {
+        self.roots.len()
+    }
+
+    pub fn root(&self, RootIndex(root_index): RootIndex) -> NodeRef {
+        NodeRef(self.roots[root_index])
+    }
+
+    pub fn roots(&self) -> impl Iterator<Item = NodeRef> + '_ {
+        self.roots.iter().map(|&node_index| NodeRef(node_index))
+    }
+
+    pub fn root_of(&self, &NodeRef(node_index): &NodeRef) -> RootIndex {
+        self.nodes[node_index].root
+    }
+
+    pub fn expand(&mut self, NodeRef(node_index): NodeRef, state: S) -> NodeRef {
+        let next_index = self.nodes.len();
+        let root = self.nodes[node_index].root;
+        self.nodes.push(ForestNode { state, prev: Some(node_index), root, });
+        NodeRef(next_index)
+    }
+
+    pub fn get_state(&self, &NodeRef(node_index): &NodeRef) -> &S {
+        &self.nodes[node_index].state
+    }
+
+    // every node of every tree
+    pub fn states(&self) -> impl Iterator<Item = (NodeRef, &S)> + '_ {
+        self.nodes.iter().enumerate().map(|(node_index, node)| (NodeRef(node_index), &node.state))
+    }
+
+    // nearest query spanning all the trees
+    pub fn closest_node<F>(&self, distance: F) -> NodeRef where F: FnMut(&S) -> f64 {
+        self.nearest(distance).map_or(NodeRef(0), |(node_ref, _)| node_ref)
+    }
+
+    pub fn path_iter<'a>(&'a self, &NodeRef(node_index): &NodeRef) -> RevPathRefIterator<'a, S> {
+        RevPathRefIterator {
+            nodes: &self.nodes,
+            node: Some(node_index),
+        }
+    }
+
+    pub fn forward_path(&self, node_ref: &NodeRef) -> RootedPath<S> where S: Clone {
+        RootedPath {
+            root: self.root_of(node_ref),
+            path: Path::from_rev_states(self.path_iter(node_ref).map(|(_, state)| state.clone())),
+        }
+    }
+
+    pub fn into_path(mut self, NodeRef(node_index): NodeRef) -> RootedPath<S> {
+        let root = self.nodes[node_index].root;
+        let mut rev_states = Vec::new();
+        let mut node = Some(node_index);
+        while let Some(node_index) = node {
+            // indices along the path only decrease, so swapping the tail in is harmless
+            let path_node = self.nodes.swap_remove(node_index);
+            node = path_node.prev;
+            rev_states.push(path_node.state);
+        }
+        RootedPath { root, path: Path::from_rev_states(rev_states), }
+    }
+}
+
+impl<S> NearestNeighbours<S> for RandomForest<S> {
+    type Ref = NodeRef;
+
+    fn visit_states<F>(&self, mut visit: F) where F: FnMut(NodeRef, &S) {
+        for (node_index, node) in self.nodes.iter().enumerate() {
+            visit(NodeRef(node_index), &node.state);
+        }
+    }
+}
+
+pub struct RevPathRefIterator<'a, S: 'a> {
+    nodes: &'a [ForestNode<S>],
+    node: Option<usize>,
+}
+
+impl<'a, S> Iterator for RevPathRefIterator<'a, S> {
+    type Item = (NodeRef, &'a S);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(node_index) = self.node {
+            let node = &self.nodes[node_index];
+            self.node = node.prev;
+            Some((NodeRef(node_index), &node.state))
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{EmptyRandomForest, RandomForest, RootIndex};
+    use PlannerInit;
+
+    #[test]
+    fn docking_bays() {
+        let planner = PlannerInit::new(EmptyRandomForest::new())
+            .add_root_ok(|empty_rtt: EmptyRandomForest<i32>| Ok(empty_rtt.add_roots(vec![-10, 0, 10]).unwrap()));
+        let mut planner_node = planner.root_node_ok(|rtt: &mut RandomForest<i32>| Ok(rtt.root(RootIndex(0))));
+        for &state in &[8, -3, 13, 6] {
+            planner_node = planner_node
+                .prepare_sample_ok(|_rtt: &mut _, _node_ref| Ok(()))
+                .sample_ok(|_rtt: &mut _| Ok(state))
+                .closest_to_sample_ok(|rtt: &mut RandomForest<i32>, sample: &i32| {
+                    Ok(rtt.closest_node(|node_state| f64::from((node_state - sample).abs())))
+                })
+                .has_transition_ok(|rtt: &mut RandomForest<i32>, node_ref, sample| Ok(rtt.expand(node_ref, sample)));
+        }
+        assert_eq!(planner_node.rtt().roots_count(), 3);
+        assert_eq!(planner_node.rtt().root_of(planner_node.node_ref()), RootIndex(2));
+
+        let forward = planner_node.rtt().forward_path(planner_node.node_ref());
+        let rooted = planner_node.into_path_ok(|rtt: RandomForest<i32>, node_ref| Ok(rtt.into_path(node_ref)));
+        assert_eq!(rooted.root, RootIndex(2));
+        assert_eq!(rooted.path.states(), &[10, 8, 6]);
+        assert_eq!(forward.path.states(), rooted.path.states());
+    }
+}
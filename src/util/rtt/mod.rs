@@ -1 +1,3 @@
 pub mod vec_slist;
+pub mod persistent;
+pub mod forest;
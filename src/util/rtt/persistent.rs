@@ -0,0 +1,285 @@
+use std::rc::Rc;
+
+use util::path::Path;
+use util::nearest::NearestNeighbours;
+
+// Same single-linked tree as `vec_slist`, but nodes live in a chain of shared segments,
+// so `fork` and `clone` are O(1) and every copy only pays for the nodes it adds. A tree
+// appends to its head segment while it is the only owner and opens a new one otherwise.
+// Segments keep a skew binary jump pointer, so lookups take O(log n) of the chain length.
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub struct NodeRef(usize);
+
+struct PathNode<S> {
+    state: S,
+    prev: Option<usize>,
+}
+
+struct Segment<S> {
+    start: usize,
+    depth: usize,
+    nodes: Vec<PathNode<S>>,
+    parent: Option<Rc<Segment<S>>>,
+    jump: Option<Rc<Segment<S>>>,
+}
+
+impl<S> Segment<S> {
+    fn new(start: usize, node: PathNode<S>, parent: Option<Rc<Segment<S>>>) -> Segment<S> {
+        let (depth, jump) = match parent {
+            None =>
+                (0, None),
+            Some(ref parent) => {
+                let jump = match (parent.jump.as_ref(), parent.jump.as_ref().and_then(|jump| jump.jump.as_ref())) {
+                    (Some(jump), Some(jump_jump)) if parent.depth - jump.depth == jump.depth - jump_jump.depth =>
+                        jump_jump.clone(),
+                    _ =>
+                        parent.clone(),
+                };
+                (parent.depth + 1, Some(jump))
+            },
+        };
+        Segment { start, depth, nodes: vec![node], parent, jump, }
+    }
+}
+
+// a long chain would otherwise be dropped recursively and could overflow the stack
+impl<S> Drop for Segment<S> {
+    fn drop(&mut self) {
+        let mut pending: Vec<_> = self.parent.take().into_iter().chain(self.jump.take()).collect();
+        while let Some(segment) = pending.pop() {
+            if let Ok(mut segment) = Rc::try_unwrap(segment) {
+                pending.extend(segment.parent.take());
+                pending.extend(segment.jump.take());
+            }
+        }
+    }
+}
+
+pub struct PersistentRandomTree<S> {
+    head: Rc<Segment<S>>,
+}
+
+impl<S> PersistentRandomTree<S> {
+    pub fn new(root_state: S) -> PersistentRandomTree<S> {
+        PersistentRandomTree {
+            head: Rc::new(Segment::new(0, PathNode { state: root_state, prev: None, }, None)),
+        }
+    }
+
+    pub fn root(&self) -> NodeRef {
+        NodeRef(0)
+    }
+
+    pub fn len(&self) -> usize {
+        self.head.start + self.head.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn expand(&mut self, NodeRef(node_index): NodeRef, state: S) -> NodeRef {
+        let next_index = self.len();
+        let node = PathNode { state, prev: Some(node_index), };
+        match Rc::get_mut(&mut self.head) {
+            Some(head) =>
+                head.nodes.push(node),
+            None => {
+                let parent = self.head.clone();
+                self.head = Rc::new(Segment::new(next_index, node, Some(parent)));
+            },
+        }
+        NodeRef(next_index)
+    }
+
+    // both trees share every node added so far and grow independently afterwards
+    pub fn fork(&self) -> PersistentRandomTree<S> {
+        self.clone()
+    }
+
+    fn node(&self, node_index: usize) -> &PathNode<S> {
+        let mut segment = &self.head;
+        while node_index < segment.start {
+            // a jump target starting after the node means every segment skipped does so as well
+            segment = match (segment.jump.as_ref(), segment.parent.as_ref()) {
+                (Some(jump), _) if node_index < jump.start =>
+                    jump,
+                (_, Some(parent)) =>
+                    parent,
+                (_, None) =>
+                    unreachable!(),
+            };
+        }
+        segment.nodes.get(node_index - segment.start)
+            .unwrap_or_else(|| panic!("node index {} is out of tree bounds", node_index))
+    }
+
+    pub fn get_state(&self, &NodeRef(node_index): &NodeRef) -> &S {
+        &self.node(node_index).state
+    }
+
+    pub fn path_iter<'a>(&'a self, &NodeRef(node_index): &NodeRef) -> RevPathRefIterator<'a, S> {
+        RevPathRefIterator {
+            tree: self,
+            node: Some(node_index),
+        }
+    }
+
+    pub fn forward_path(&self, node_ref: &NodeRef) -> Path<S> where S: Clone {
+        Path::from_rev_states(self.path_iter(node_ref).map(|(_, state)| state.clone()))
+    }
+
+    pub fn states(&self) -> PersistentRandomTreeStates<'_, S> {
+        PersistentRandomTreeStates {
+            tree: self,
+            index: 0,
+        }
+    }
+
+    pub fn closest_node<F>(&self, distance: F) -> NodeRef where F: FnMut(&S) -> f64 {
+        self.nearest(distance).map_or(self.root(), |(node_ref, _)| node_ref)
+    }
+}
+
+impl<S> Clone for PersistentRandomTree<S> {
+    fn clone(&self) -> PersistentRandomTree<S> {
+        PersistentRandomTree { head: self.head.clone(), }
+    }
+}
+
+impl<S> NearestNeighbours<S> for PersistentRandomTree<S> {
+    type Ref = NodeRef;
+
+    fn visit_states<F>(&self, mut visit: F) where F: FnMut(NodeRef, &S) {
+        for (node_ref, state) in self.states() {
+            visit(node_ref, state);
+        }
+    }
+}
+
+pub struct PersistentRandomTreeStates<'a, S: 'a> {
+    tree: &'a PersistentRandomTree<S>,
+    index: usize,
+}
+
+impl<'a, S> Iterator for PersistentRandomTreeStates<'a, S> {
+    type Item = (NodeRef, &'a S);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.index >= self.tree.len() {
+            None
+        } else {
+            let item = (NodeRef(self.index), &self.tree.node(self.index).state);
+            self.index += 1;
+            Some(item)
+        }
+    }
+}
+
+pub struct RevPathRefIterator<'a, S: 'a> {
+    tree: &'a PersistentRandomTree<S>,
+    node: Option<usize>,
+}
+
+impl<'a, S> Iterator for RevPathRefIterator<'a, S> {
+    type Item = (NodeRef, &'a S);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(node_index) = self.node {
+            let node = self.tree.node(node_index);
+            self.node = node.prev;
+            Some((NodeRef(node_index), &node.state))
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{PersistentRandomTree, NodeRef};
+    use {PlannerInit, PlannerRttNode};
+
+    #[test]
+    fn speculative_forks() {
+        let planner = PlannerInit::new(0)
+            .add_root_ok(|root| Ok(PersistentRandomTree::new(root)));
+        let planner_node = planner.root_node_ok(|rtt: &mut PersistentRandomTree<i32>| {
+            let node_ref = rtt.root();
+            Ok(rtt.expand(node_ref, 1))
+        });
+
+        let extend = |planner_node: PlannerRttNode<PersistentRandomTree<i32>, _>, state| {
+            planner_node
+                .prepare_sample_ok(|_rtt: &mut _, _node_ref| Ok(()))
+                .sample_ok(|_rtt: &mut _| Ok(state))
+                .closest_to_sample_ok(|rtt: &mut PersistentRandomTree<i32>, sample: &i32| {
+                    Ok(rtt.closest_node(|node_state| f64::from((node_state - sample).abs())))
+                })
+                .has_transition_ok(|rtt: &mut PersistentRandomTree<i32>, node_ref, sample| Ok(rtt.expand(node_ref, sample)))
+        };
+
+        let planner_node = extend(planner_node, 5);
+        let mut forks: Vec<_> = (0 .. 3).map(|_| planner_node.clone()).collect();
+        let mut rtt = planner_node.into_path_ok(|rtt, _node_ref| Ok(rtt));
+        let mut branch = rtt.fork();
+        let branch_node = branch.expand(branch.root(), -7);
+        let trunk_node = rtt.expand(rtt.root(), 9);
+        assert_eq!(branch_node, trunk_node);
+        assert_eq!(branch.get_state(&branch_node), &-7);
+        assert_eq!(rtt.get_state(&trunk_node), &9);
+        assert_eq!(rtt.forward_path(&trunk_node).states(), &[0, 9]);
+
+        let paths: Vec<_> = forks.drain(..).zip(vec![4, 10, -1])
+            .map(|(planner_node, state)| {
+                extend(planner_node, state).into_path_ok(|rtt: PersistentRandomTree<i32>, node_ref| Ok(rtt.forward_path(&node_ref).into_states()))
+            })
+            .collect();
+        assert_eq!(paths, vec![vec![0, 1, 5, 4], vec![0, 1, 5, 10], vec![0, -1]]);
+    }
+
+    #[test]
+    fn fork_and_mutate_both() {
+        let mut trunk = PersistentRandomTree::new(0);
+        let mut last = trunk.root();
+        let mut branches = Vec::new();
+        for step in 1 .. 2000 {
+            let mut branch = trunk.fork();
+            let branch_node = branch.expand(last, -step);
+            last = trunk.expand(last, step);
+            assert_eq!(branch_node, last);
+            branches.push((branch, branch_node));
+        }
+        assert_eq!(trunk.len(), 2000);
+        assert_eq!(trunk.forward_path(&last).states(), &(0 .. 2000).collect::<Vec<_>>()[..]);
+        for (step, (branch, branch_node)) in branches.iter_mut().enumerate() {
+            let step = step as i32 + 1;
+            assert_eq!(branch.len(), step as usize + 1);
+            assert_eq!(branch.get_state(branch_node), &-step);
+            assert_eq!(branch.path_iter(branch_node).nth(1).map(|(_, &state)| state), Some(step - 1));
+            let node = branch.expand(*branch_node, 0);
+            assert_eq!(branch.get_state(&node), &0);
+        }
+        assert_eq!(trunk.get_state(&last), &1999);
+
+        let clone = trunk.clone();
+        let next = trunk.expand(last, 2000);
+        assert_eq!(clone.len(), 2000);
+        assert_eq!(trunk.len(), 2001);
+        assert_eq!(trunk.get_state(&next), &2000);
+    }
+
+    #[test]
+    fn long_chain_drop() {
+        let mut rtt = PersistentRandomTree::new(0);
+        let mut last = rtt.root();
+        for step in 1 .. 200000 {
+            let fork = rtt.fork();
+            last = rtt.expand(last, step);
+            drop(fork);
+        }
+        assert_eq!(rtt.get_state(&NodeRef(0)), &0);
+        assert_eq!(rtt.get_state(&last), &199999);
+    }
+}
@@ -1,8 +1,12 @@
 use std::marker::PhantomData;
 
+use util::path::Path;
+use util::nearest::NearestNeighbours;
+
 #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
 pub struct NodeRef(usize);
 
+#[derive(Clone)]
 struct PathNode<S> {
     state: S,
     prev: Option<usize>,
@@ -26,6 +30,19 @@ impl<S> EmptyRandomTree<S> {
     }
 }
 
+impl<S> Clone for EmptyRandomTree<S> {
+    fn clone(&self) -> EmptyRandomTree<S> {
+        EmptyRandomTree::new()
+    }
+}
+
+impl<S> Default for EmptyRandomTree<S> {
+    fn default() -> EmptyRandomTree<S> {
+        EmptyRandomTree::new()
+    }
+}
+
+#[derive(Clone)]
 pub struct RandomTree<S> {
     nodes: Vec<PathNode<S>>,
 }
@@ -55,11 +72,20 @@ impl<S> RandomTree<S> {
         }
     }
 
+    pub fn forward_path(&self, node_ref: &NodeRef) -> Path<S> where S: Clone {
+        Path::from_rev_states(self.path_iter(node_ref).map(|(_, state)| state.clone()))
+    }
+
+    // node minimizing `distance`, e.g. the one closest to the goal when planning has to be given up
+    pub fn closest_node<F>(&self, distance: F) -> NodeRef where F: FnMut(&S) -> f64 {
+        self.nearest(distance).map_or(self.root(), |(node_ref, _)| node_ref)
+    }
+
     pub fn get_state(&self, &NodeRef(node_index): &NodeRef) -> &S {
         &self.nodes[node_index].state
     }
 
-    pub fn states(&self) -> RandomTreeStates<S> {
+    pub fn states(&self) -> RandomTreeStates<'_, S> {
         RandomTreeStates {
             root: (NodeRef(0), &self.nodes[0].state),
             children: RandomTreeStatesIter {
@@ -70,6 +96,16 @@ impl<S> RandomTree<S> {
     }
 }
 
+impl<S> NearestNeighbours<S> for RandomTree<S> {
+    type Ref = NodeRef;
+
+    fn visit_states<F>(&self, mut visit: F) where F: FnMut(NodeRef, &S) {
+        for (node_index, node) in self.nodes.iter().enumerate() {
+            visit(NodeRef(node_index), &node.state);
+        }
+    }
+}
+
 pub struct RandomTreeStates<'a, S: 'a> {
     pub root: (NodeRef, &'a S),
     pub children: RandomTreeStatesIter<'a, S>,
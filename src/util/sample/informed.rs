@@ -0,0 +1,255 @@
+use super::{Sampler, RandomSource};
+use super::uniform::UniformSampler;
+use TransSample;
+use util::NeverError;
+use util::goal::{GoalRegion, BallGoal};
+use util::space::{Bounds, euclidean_distance};
+
+// Provided by a rtt which keeps track of the best solution found so far.
+pub trait BestCost {
+    fn best_cost(&self) -> Option<f64>;
+}
+
+// CostTracking
+
+// Adds `BestCost` to any rtt: report the cost of every path found, e.g. from the goal
+// check, and the informed sampler tightens on the next `sample` transition.
+#[derive(Clone, Debug)]
+pub struct CostTracking<RT> {
+    rtt: RT,
+    best_cost: Option<f64>,
+}
+
+impl<RT> CostTracking<RT> {
+    pub fn new(rtt: RT) -> CostTracking<RT> {
+        CostTracking { rtt, best_cost: None, }
+    }
+
+    pub fn get_ref(&self) -> &RT {
+        &self.rtt
+    }
+
+    pub fn get_mut(&mut self) -> &mut RT {
+        &mut self.rtt
+    }
+
+    pub fn into_inner(self) -> RT {
+        self.rtt
+    }
+
+    // costs are only allowed to decrease, a worse cost is ignored
+    pub fn report_cost(&mut self, cost: f64) {
+        match self.best_cost {
+            Some(best) if best <= cost =>
+                (),
+            _ =>
+                self.best_cost = Some(cost),
+        }
+    }
+}
+
+impl<RT> BestCost for CostTracking<RT> {
+    fn best_cost(&self) -> Option<f64> {
+        self.best_cost
+    }
+}
+
+// InformedSampler
+
+pub struct InformedSampler<R> {
+    uniform: UniformSampler<R>,
+    unit_ball: BallGoal,
+    start: Vec<f64>,
+    goal: Vec<f64>,
+    center: Vec<f64>,
+    axis: Vec<f64>,
+    min_cost: f64,
+    best_cost: Option<f64>,
+    max_attempts: usize,
+}
+
+impl<R> InformedSampler<R> where R: RandomSource {
+    pub fn new(bounds: Bounds, rng: R, start: Vec<f64>, goal: Vec<f64>) -> InformedSampler<R> {
+        assert_eq!(bounds.dimension(), start.len(), "start state dimension mismatch");
+        assert_eq!(bounds.dimension(), goal.len(), "goal state dimension mismatch");
+        let min_cost = euclidean_distance(&start, &goal);
+        let center = start.iter().zip(goal.iter()).map(|(s, g)| (s + g) / 2.0).collect();
+        let axis = start.iter()
+            .zip(goal.iter())
+            .map(|(s, g)| if min_cost > 0.0 { (g - s) / min_cost } else { 0.0 })
+            .collect();
+        InformedSampler {
+            unit_ball: BallGoal::new(vec![0.0; bounds.dimension()], 1.0),
+            uniform: UniformSampler::new(bounds, rng),
+            start, goal, center, axis, min_cost,
+            best_cost: None,
+            max_attempts: 100,
+        }
+    }
+
+    // when no sample inside bounds is found after `max_attempts` tries a plain uniform sample is returned
+    pub fn max_attempts(mut self, max_attempts: usize) -> InformedSampler<R> {
+        self.max_attempts = max_attempts;
+        self
+    }
+
+    pub fn start(&self) -> &[f64] {
+        &self.start
+    }
+
+    pub fn goal(&self) -> &[f64] {
+        &self.goal
+    }
+
+    pub fn best_cost(&self) -> Option<f64> {
+        self.best_cost
+    }
+
+    // costs are only allowed to decrease, a worse cost is ignored
+    pub fn update_cost(&mut self, cost: f64) {
+        match self.best_cost {
+            Some(best) if best <= cost =>
+                (),
+            _ =>
+                self.best_cost = Some(cost),
+        }
+    }
+
+    pub fn as_cost_tracking_trans(&mut self) -> InformedTrans<'_, R> {
+        InformedTrans { sampler: self, }
+    }
+
+    fn sample_ellipsoid(&mut self, cost: f64) -> Vec<f64> {
+        let dimension = self.unit_ball.center.len();
+        let transverse_radius = cost / 2.0;
+        let conjugate_radius = (cost * cost - self.min_cost * self.min_cost).max(0.0).sqrt() / 2.0;
+        let mut point = self.unit_ball.sample(self.uniform.rng_mut())
+            .expect("ball goal is always sampled");
+        point[0] *= transverse_radius;
+        for value in point.iter_mut().skip(1) {
+            *value *= conjugate_radius;
+        }
+        // householder reflection mapping the first basis vector onto the start-goal axis
+        let mut reflect: Vec<f64> = self.axis.iter().map(|v| -v).collect();
+        reflect[0] += 1.0;
+        let reflect_sq = reflect.iter().map(|v| v * v).sum::<f64>();
+        if reflect_sq > 1e-12 {
+            let dot = reflect.iter().zip(point.iter()).map(|(r, p)| r * p).sum::<f64>();
+            let factor = 2.0 * dot / reflect_sq;
+            for axis in 0 .. dimension {
+                point[axis] -= factor * reflect[axis];
+            }
+        }
+        point.iter().zip(self.center.iter()).map(|(p, c)| p + c).collect()
+    }
+}
+
+impl<R> Sampler for InformedSampler<R> where R: RandomSource {
+    type Sample = Vec<f64>;
+
+    fn sample(&mut self) -> Vec<f64> {
+        match self.best_cost {
+            Some(cost) if cost.is_finite() => {
+                for _ in 0 .. self.max_attempts {
+                    let state = self.sample_ellipsoid(cost);
+                    if self.uniform.bounds().contains(&state) {
+                        return state;
+                    }
+                }
+                self.uniform.sample()
+            },
+            _ =>
+                self.uniform.sample(),
+        }
+    }
+}
+
+// InformedTrans
+
+pub struct InformedTrans<'a, R: 'a> {
+    sampler: &'a mut InformedSampler<R>,
+}
+
+impl<'a, RT, R> TransSample<RT> for InformedTrans<'a, R> where RT: BestCost, R: RandomSource {
+    type Sample = Vec<f64>;
+    type Error = NeverError;
+
+    fn sample(self, rtt: &mut RT) -> Result<Self::Sample, Self::Error> {
+        if let Some(cost) = rtt.best_cost() {
+            self.sampler.update_cost(cost);
+        }
+        Ok(self.sampler.sample())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use {PlannerInit, PlannerGoalCheck};
+    use util::testing::xorshift;
+
+    #[test]
+    fn samples_inside_ellipsoid() {
+        let bounds = Bounds::new(vec![-10.0, -10.0, -10.0], vec![10.0, 10.0, 10.0]);
+        let start = vec![-1.0, 1.0, 0.5];
+        let goal = vec![2.0, -1.0, 1.5];
+        let mut sampler = InformedSampler::new(bounds, xorshift(7), start.clone(), goal.clone());
+        sampler.update_cost(5.0);
+        sampler.update_cost(6.0);
+        assert_eq!(sampler.best_cost(), Some(5.0));
+        for _ in 0 .. 1000 {
+            let state = sampler.sample();
+            let cost = euclidean_distance(&start, &state) + euclidean_distance(&state, &goal);
+            assert!(cost <= 5.0 + 1e-9);
+        }
+    }
+
+    #[test]
+    fn tightens_from_cost_tracking_rtt() {
+        let bounds = Bounds::new(vec![0.0, 0.0], vec![10.0, 10.0]);
+        let (start, goal) = (vec![1.0, 1.0], vec![9.0, 1.0]);
+        let mut sampler = InformedSampler::new(bounds, xorshift(13), start.clone(), goal.clone());
+        let planner_node = PlannerInit::new(start.clone())
+            .add_root_ok(|root| Ok(CostTracking::new(vec![root])))
+            .root_node_ok(|_rtt: &mut _| Ok(0));
+        // the goal check reports the cost of the path through the node it accepts
+        let planner_goal = match planner_node.goal_check_ok(|rtt: &CostTracking<Vec<Vec<f64>>>, &node_ref: &usize| {
+            Ok(euclidean_distance(&rtt.get_ref()[node_ref], &goal) <= 9.0)
+        }) {
+            PlannerGoalCheck::Reached(planner_goal) => planner_goal,
+            PlannerGoalCheck::NotReached(..) => panic!("goal should be reached"),
+        };
+        let planner_node = planner_goal.into_rtt_node();
+        let planner_ready = planner_node.prepare_sample_ok(|rtt: &mut CostTracking<Vec<Vec<f64>>>, node_ref: usize| {
+            let cost = euclidean_distance(&rtt.get_ref()[node_ref], &goal) + 1.0;
+            rtt.report_cost(cost);
+            Ok(())
+        });
+        let planner_sample = planner_ready.sample_ok(sampler.as_cost_tracking_trans());
+        let state = planner_sample.sample();
+        assert_eq!(sampler.best_cost(), Some(9.0));
+        assert!(euclidean_distance(&start, state) + euclidean_distance(state, &goal) <= 9.0 + 1e-9);
+    }
+
+    #[test]
+    fn tracks_rtt_cost() {
+        struct Rtt(Option<f64>);
+
+        impl BestCost for Rtt {
+            fn best_cost(&self) -> Option<f64> {
+                self.0
+            }
+        }
+
+        let bounds = Bounds::new(vec![0.0, 0.0], vec![10.0, 10.0]);
+        let mut sampler = InformedSampler::new(bounds, xorshift(11), vec![1.0, 1.0], vec![9.0, 1.0]);
+        let planner = PlannerInit::new(Rtt(None))
+            .add_root_ok(|mut rtt: Rtt| { rtt.0 = Some(8.5); Ok(rtt) })
+            .root_node_ok(|_rtt: &mut _| Ok(()))
+            .prepare_sample_ok(|_rtt: &mut _, ()| Ok(()));
+        let planner_sample = planner.sample_ok(sampler.as_cost_tracking_trans());
+        let state = planner_sample.sample();
+        assert!(euclidean_distance(&[1.0, 1.0], state) + euclidean_distance(state, &[9.0, 1.0]) <= 8.5 + 1e-9);
+        assert_eq!(sampler.best_cost(), Some(8.5));
+    }
+}
@@ -0,0 +1,82 @@
+use super::Sampler;
+use util::grid::sdf::SignedDistanceField;
+
+// Retracts base samples toward the medial axis by climbing the distance field gradient
+// while the clearance keeps growing.
+pub struct MedialAxisSampler<'a, B> {
+    base: B,
+    sdf: &'a SignedDistanceField,
+    step: f64,
+    max_steps: usize,
+}
+
+impl<'a, B> MedialAxisSampler<'a, B> where B: Sampler<Sample = Vec<f64>> {
+    pub fn new(base: B, sdf: &'a SignedDistanceField, step: f64, max_steps: usize) -> MedialAxisSampler<'a, B> {
+        assert!(step > 0.0, "retraction step should be positive");
+        MedialAxisSampler { base, sdf, step, max_steps, }
+    }
+
+    pub fn retract(&self, mut state: Vec<f64>) -> Vec<f64> {
+        let mut clearance = self.sdf.distance(&state);
+        for _ in 0 .. self.max_steps {
+            let gradient = self.sdf.gradient(&state);
+            let norm = (gradient[0] * gradient[0] + gradient[1] * gradient[1]).sqrt();
+            if norm < 1e-9 {
+                break;
+            }
+            let mut next = state.clone();
+            next[0] += self.step * gradient[0] / norm;
+            next[1] += self.step * gradient[1] / norm;
+            let next_clearance = self.sdf.distance(&next);
+            if next_clearance <= clearance {
+                break;
+            }
+            state = next;
+            clearance = next_clearance;
+        }
+        state
+    }
+}
+
+impl<'a, B> Sampler for MedialAxisSampler<'a, B> where B: Sampler<Sample = Vec<f64>> {
+    type Sample = Vec<f64>;
+
+    fn sample(&mut self) -> Vec<f64> {
+        let state = self.base.sample();
+        self.retract(state)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use util::grid::OccupancyGrid;
+    use util::sample::uniform::UniformSampler;
+    use util::space::Bounds;
+    use util::testing::xorshift;
+
+    #[test]
+    fn retraction_gains_clearance() {
+        let mut grid = OccupancyGrid::new(12, 24);
+        for row in 0 .. 8 {
+            grid.set_occupied(&(row, 12), true);
+        }
+        let sdf = SignedDistanceField::new(&grid);
+        let bounds = Bounds::new(vec![0.0, 0.0], vec![24.0, 12.0]);
+        let mut base = UniformSampler::new(bounds.clone(), xorshift(13));
+        let sampler = MedialAxisSampler::new(UniformSampler::new(bounds, xorshift(17)), &sdf, 0.25, 40);
+
+        let mut improved = 0;
+        for _ in 0 .. 200 {
+            let state = base.sample();
+            let clearance = sdf.distance(&state);
+            let retracted = sampler.retract(state);
+            let retracted_clearance = sdf.distance(&retracted);
+            assert!(retracted_clearance >= clearance);
+            if clearance > 0.0 && retracted_clearance > clearance + 0.5 {
+                improved += 1;
+            }
+        }
+        assert!(improved > 100, "only {} samples moved away from obstacles", improved);
+    }
+}
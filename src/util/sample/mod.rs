@@ -0,0 +1,53 @@
+use std::f64::consts::PI;
+
+use TransSample;
+use util::NeverError;
+
+pub mod quasi;
+pub mod narrow;
+pub mod informed;
+pub mod medial;
+pub mod uniform;
+
+pub trait Sampler {
+    type Sample;
+
+    fn sample(&mut self) -> Self::Sample;
+
+    fn as_trans(&mut self) -> SamplerTrans<'_, Self> where Self: Sized {
+        SamplerTrans { sampler: self, }
+    }
+}
+
+pub struct SamplerTrans<'a, T: 'a> {
+    sampler: &'a mut T,
+}
+
+impl<'a, RT, T> TransSample<RT> for SamplerTrans<'a, T> where T: Sampler {
+    type Sample = T::Sample;
+    type Error = NeverError;
+
+    fn sample(self, _rtt: &mut RT) -> Result<Self::Sample, Self::Error> {
+        Ok(self.sampler.sample())
+    }
+}
+
+// RandomSource
+
+pub trait RandomSource {
+    // uniformly distributed value in `[0, 1)`
+    fn next_unit(&mut self) -> f64;
+
+    fn next_normal(&mut self) -> f64 {
+        // Box-Muller transform
+        let u = 1.0 - self.next_unit();
+        let v = self.next_unit();
+        (-2.0 * u.ln()).sqrt() * (2.0 * PI * v).cos()
+    }
+}
+
+impl<F> RandomSource for F where F: FnMut() -> f64 {
+    fn next_unit(&mut self) -> f64 {
+        (self)()
+    }
+}
@@ -0,0 +1,169 @@
+use super::{Sampler, RandomSource};
+use util::validity::StateValidityChecker;
+
+fn gaussian_neighbour<R>(rng: &mut R, state: &[f64], sigma: f64) -> Vec<f64> where R: RandomSource {
+    state.iter().map(|value| value + rng.next_normal() * sigma).collect()
+}
+
+// GaussianSampler
+
+// Samples near obstacle boundaries: a pair of points at normally distributed distance
+// is drawn and the valid one is kept only when its partner is invalid.
+pub struct GaussianSampler<B, VC, R> {
+    base: B,
+    checker: VC,
+    rng: R,
+    sigma: f64,
+    max_attempts: usize,
+}
+
+impl<B, VC, R> GaussianSampler<B, VC, R>
+    where B: Sampler<Sample = Vec<f64>>,
+          VC: StateValidityChecker<Vec<f64>>,
+          R: RandomSource,
+{
+    pub fn new(base: B, checker: VC, rng: R, sigma: f64) -> GaussianSampler<B, VC, R> {
+        GaussianSampler { base, checker, rng, sigma, max_attempts: 100, }
+    }
+
+    // when no boundary sample is found after `max_attempts` tries a plain base sample is returned
+    pub fn max_attempts(mut self, max_attempts: usize) -> GaussianSampler<B, VC, R> {
+        self.max_attempts = max_attempts;
+        self
+    }
+
+    pub fn try_sample(&mut self) -> Option<Vec<f64>> {
+        let state_a = self.base.sample();
+        let state_b = gaussian_neighbour(&mut self.rng, &state_a, self.sigma);
+        match (self.checker.is_valid(&state_a), self.checker.is_valid(&state_b)) {
+            (true, false) => Some(state_a),
+            (false, true) => Some(state_b),
+            _ => None,
+        }
+    }
+}
+
+impl<B, VC, R> Sampler for GaussianSampler<B, VC, R>
+    where B: Sampler<Sample = Vec<f64>>,
+          VC: StateValidityChecker<Vec<f64>>,
+          R: RandomSource,
+{
+    type Sample = Vec<f64>;
+
+    fn sample(&mut self) -> Vec<f64> {
+        for _ in 0 .. self.max_attempts {
+            if let Some(state) = self.try_sample() {
+                return state;
+            }
+        }
+        self.base.sample()
+    }
+}
+
+// BridgeSampler
+
+// Samples inside narrow passages: two invalid points at normally distributed distance
+// whose midpoint is valid form a "bridge" across the passage.
+pub struct BridgeSampler<B, VC, R> {
+    base: B,
+    checker: VC,
+    rng: R,
+    sigma: f64,
+    max_attempts: usize,
+}
+
+impl<B, VC, R> BridgeSampler<B, VC, R>
+    where B: Sampler<Sample = Vec<f64>>,
+          VC: StateValidityChecker<Vec<f64>>,
+          R: RandomSource,
+{
+    pub fn new(base: B, checker: VC, rng: R, sigma: f64) -> BridgeSampler<B, VC, R> {
+        BridgeSampler { base, checker, rng, sigma, max_attempts: 100, }
+    }
+
+    // when no bridge is found after `max_attempts` tries a plain base sample is returned
+    pub fn max_attempts(mut self, max_attempts: usize) -> BridgeSampler<B, VC, R> {
+        self.max_attempts = max_attempts;
+        self
+    }
+
+    pub fn try_sample(&mut self) -> Option<Vec<f64>> {
+        let state_a = self.base.sample();
+        if self.checker.is_valid(&state_a) {
+            return None;
+        }
+        let state_b = gaussian_neighbour(&mut self.rng, &state_a, self.sigma);
+        if self.checker.is_valid(&state_b) {
+            return None;
+        }
+        let middle: Vec<_> = state_a.iter()
+            .zip(state_b.iter())
+            .map(|(a, b)| (a + b) / 2.0)
+            .collect();
+        if self.checker.is_valid(&middle) {
+            Some(middle)
+        } else {
+            None
+        }
+    }
+}
+
+impl<B, VC, R> Sampler for BridgeSampler<B, VC, R>
+    where B: Sampler<Sample = Vec<f64>>,
+          VC: StateValidityChecker<Vec<f64>>,
+          R: RandomSource,
+{
+    type Sample = Vec<f64>;
+
+    fn sample(&mut self) -> Vec<f64> {
+        for _ in 0 .. self.max_attempts {
+            if let Some(state) = self.try_sample() {
+                return state;
+            }
+        }
+        self.base.sample()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use util::space::Bounds;
+    use util::sample::uniform::UniformSampler;
+    use util::testing::xorshift;
+
+    // unit square split by a wall at `x in [0.45, 0.55]` with a narrow door at `y in [0.5, 0.52]`
+    fn corridor(state: &[f64]) -> bool {
+        !(state[0] >= 0.45 && state[0] <= 0.55 && (state[1] < 0.5 || state[1] > 0.52))
+    }
+
+    fn base(seed: u64) -> UniformSampler<impl FnMut() -> f64> {
+        UniformSampler::new(Bounds::new(vec![0.0, 0.0], vec![1.0, 1.0]), xorshift(seed))
+    }
+
+    #[test]
+    fn gaussian_near_walls() {
+        let mut sampler = GaussianSampler::new(base(1), |s: &Vec<f64>| corridor(s), xorshift(2), 0.02);
+        for _ in 0 .. 100 {
+            let state = sampler.try_sample();
+            if let Some(state) = state {
+                assert!(corridor(&state));
+                assert!(state[0] > 0.35 && state[0] < 0.65);
+            }
+        }
+    }
+
+    #[test]
+    fn bridge_in_door() {
+        let mut sampler = BridgeSampler::new(base(3), |s: &Vec<f64>| corridor(s), xorshift(4), 0.05);
+        let mut found = 0;
+        for _ in 0 .. 10000 {
+            if let Some(state) = sampler.try_sample() {
+                assert!(state[0] >= 0.45 && state[0] <= 0.55);
+                assert!(state[1] >= 0.5 && state[1] <= 0.52);
+                found += 1;
+            }
+        }
+        assert!(found > 0);
+    }
+}
@@ -0,0 +1,301 @@
+use super::{Sampler, RandomSource};
+use util::space::Bounds;
+
+pub trait UnitSequence {
+    fn dimension(&self) -> usize;
+
+    fn next_point(&mut self, point: &mut [f64]);
+}
+
+// Halton
+
+#[derive(Clone, Debug)]
+pub struct Halton {
+    bases: Vec<u64>,
+    index: u64,
+}
+
+impl Halton {
+    pub fn new(bases: Vec<u64>) -> Halton {
+        assert!(bases.iter().all(|&base| base >= 2), "halton base should be at least 2");
+        Halton { bases, index: 0, }
+    }
+
+    pub fn with_dimension(dimension: usize) -> Halton {
+        Halton::new(first_primes(dimension))
+    }
+
+    pub fn skip(&mut self, count: u64) {
+        self.index += count;
+    }
+}
+
+impl UnitSequence for Halton {
+    fn dimension(&self) -> usize {
+        self.bases.len()
+    }
+
+    fn next_point(&mut self, point: &mut [f64]) {
+        // zero point is skipped as it lies on the corner of the unit cube
+        self.index += 1;
+        for (value, &base) in point.iter_mut().zip(self.bases.iter()) {
+            *value = radical_inverse(self.index, base);
+        }
+    }
+}
+
+fn radical_inverse(mut index: u64, base: u64) -> f64 {
+    let inv_base = 1.0 / base as f64;
+    let mut factor = inv_base;
+    let mut value = 0.0;
+    while index > 0 {
+        value += (index % base) as f64 * factor;
+        index /= base;
+        factor *= inv_base;
+    }
+    value
+}
+
+fn first_primes(count: usize) -> Vec<u64> {
+    let mut primes: Vec<u64> = Vec::with_capacity(count);
+    let mut candidate = 2;
+    while primes.len() < count {
+        if primes.iter().take_while(|&&p| p * p <= candidate).all(|&p| candidate % p != 0) {
+            primes.push(candidate);
+        }
+        candidate += 1;
+    }
+    primes
+}
+
+// Sobol
+
+const SOBOL_BITS: usize = 32;
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct DirectionNumbers {
+    // degree of primitive polynomial is `m.len()`
+    pub a: u32,
+    pub m: Vec<u32>,
+}
+
+// Joe & Kuo `new-joe-kuo-6.21201` initial direction numbers for dimensions 2 and above
+const JOE_KUO: &[(u32, &[u32])] = &[
+    (0, &[1]),
+    (1, &[1, 3]),
+    (1, &[1, 3, 1]),
+    (2, &[1, 1, 1]),
+    (1, &[1, 1, 3, 3]),
+    (4, &[1, 3, 5, 13]),
+    (2, &[1, 1, 5, 5, 17]),
+    (4, &[1, 1, 5, 5, 5]),
+    (7, &[1, 1, 7, 11, 19]),
+    (11, &[1, 1, 5, 1, 1]),
+    (13, &[1, 1, 1, 3, 11]),
+    (14, &[1, 3, 5, 5, 31]),
+    (1, &[1, 3, 3, 9, 7, 49]),
+    (13, &[1, 1, 1, 15, 21, 21]),
+    (16, &[1, 3, 1, 13, 27, 49]),
+];
+
+pub const SOBOL_MAX_BUILTIN_DIMENSION: usize = 1 + 15;
+
+#[derive(Clone, Debug)]
+pub struct Sobol {
+    directions: Vec<[u32; SOBOL_BITS]>,
+    state: Vec<u32>,
+    index: u64,
+}
+
+impl Sobol {
+    pub fn new(dimension: usize) -> Sobol {
+        assert!(
+            dimension <= SOBOL_MAX_BUILTIN_DIMENSION,
+            "only {} builtin sobol dimensions available, use `with_direction_numbers` for more",
+            SOBOL_MAX_BUILTIN_DIMENSION,
+        );
+        let numbers = JOE_KUO.iter()
+            .take(dimension.saturating_sub(1))
+            .map(|&(a, m)| DirectionNumbers { a, m: m.to_vec(), })
+            .collect();
+        Sobol::with_direction_numbers(dimension, numbers)
+    }
+
+    // direction numbers are given for dimensions 2 and above, first dimension is always van der Corput
+    pub fn with_direction_numbers(dimension: usize, numbers: Vec<DirectionNumbers>) -> Sobol {
+        assert_eq!(numbers.len() + 1, dimension.max(1), "direction numbers count mismatch");
+        let mut directions = Vec::with_capacity(dimension);
+        if dimension > 0 {
+            let mut first = [0; SOBOL_BITS];
+            for (k, v) in first.iter_mut().enumerate() {
+                *v = 1 << (SOBOL_BITS - 1 - k);
+            }
+            directions.push(first);
+        }
+        for DirectionNumbers { a, m, } in numbers {
+            let degree = m.len();
+            assert!(degree > 0 && degree <= SOBOL_BITS, "invalid primitive polynomial degree");
+            let mut v = [0; SOBOL_BITS];
+            for k in 0 .. SOBOL_BITS {
+                v[k] = if k < degree {
+                    m[k] << (SOBOL_BITS - 1 - k)
+                } else {
+                    let mut value = v[k - degree] ^ (v[k - degree] >> degree);
+                    for i in 1 .. degree {
+                        if (a >> (degree - 1 - i)) & 1 == 1 {
+                            value ^= v[k - i];
+                        }
+                    }
+                    value
+                };
+            }
+            directions.push(v);
+        }
+        Sobol { state: vec![0; directions.len()], directions, index: 0, }
+    }
+}
+
+impl UnitSequence for Sobol {
+    fn dimension(&self) -> usize {
+        self.directions.len()
+    }
+
+    fn next_point(&mut self, point: &mut [f64]) {
+        // gray code construction, zero point is skipped
+        let bit = (!self.index).trailing_zeros() as usize;
+        assert!(bit < SOBOL_BITS, "sobol sequence exhausted");
+        self.index += 1;
+        for ((value, state), v) in point.iter_mut().zip(self.state.iter_mut()).zip(self.directions.iter()) {
+            *state ^= v[bit];
+            *value = *state as f64 / (1u64 << SOBOL_BITS) as f64;
+        }
+    }
+}
+
+// CranleyPatterson
+
+#[derive(Clone, Debug)]
+pub struct CranleyPatterson<Q> {
+    sequence: Q,
+    shift: Vec<f64>,
+}
+
+impl<Q> CranleyPatterson<Q> where Q: UnitSequence {
+    pub fn new(sequence: Q, shift: Vec<f64>) -> CranleyPatterson<Q> {
+        assert_eq!(sequence.dimension(), shift.len(), "rotation shift dimension mismatch");
+        CranleyPatterson { sequence, shift, }
+    }
+
+    pub fn random<R>(sequence: Q, rng: &mut R) -> CranleyPatterson<Q> where R: RandomSource {
+        let shift = (0 .. sequence.dimension()).map(|_| rng.next_unit()).collect();
+        CranleyPatterson::new(sequence, shift)
+    }
+}
+
+impl<Q> UnitSequence for CranleyPatterson<Q> where Q: UnitSequence {
+    fn dimension(&self) -> usize {
+        self.sequence.dimension()
+    }
+
+    fn next_point(&mut self, point: &mut [f64]) {
+        self.sequence.next_point(point);
+        for (value, shift) in point.iter_mut().zip(self.shift.iter()) {
+            *value = (*value + shift).fract();
+        }
+    }
+}
+
+// QuasiRandomSampler
+
+#[derive(Clone, Debug)]
+pub struct QuasiRandomSampler<Q> {
+    sequence: Q,
+    bounds: Bounds,
+}
+
+impl<Q> QuasiRandomSampler<Q> where Q: UnitSequence {
+    pub fn new(sequence: Q, bounds: Bounds) -> QuasiRandomSampler<Q> {
+        assert_eq!(sequence.dimension(), bounds.dimension(), "sequence and bounds dimensions mismatch");
+        QuasiRandomSampler { sequence, bounds, }
+    }
+
+    pub fn bounds(&self) -> &Bounds {
+        &self.bounds
+    }
+}
+
+impl<Q> Sampler for QuasiRandomSampler<Q> where Q: UnitSequence {
+    type Sample = Vec<f64>;
+
+    fn sample(&mut self) -> Vec<f64> {
+        let mut point = vec![0.0; self.sequence.dimension()];
+        self.sequence.next_point(&mut point);
+        self.bounds.from_unit(&point)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn points<Q>(mut sequence: Q, count: usize) -> Vec<Vec<f64>> where Q: UnitSequence {
+        (0 .. count)
+            .map(|_| {
+                let mut point = vec![0.0; sequence.dimension()];
+                sequence.next_point(&mut point);
+                point
+            })
+            .collect()
+    }
+
+    #[test]
+    fn halton_2_3() {
+        let seq = points(Halton::with_dimension(2), 4);
+        let expected = [[0.5, 1.0 / 3.0], [0.25, 2.0 / 3.0], [0.75, 1.0 / 9.0], [0.125, 4.0 / 9.0]];
+        for (point, expected) in seq.iter().zip(expected.iter()) {
+            assert!((point[0] - expected[0]).abs() < 1e-12);
+            assert!((point[1] - expected[1]).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn sobol_first_points() {
+        let seq = points(Sobol::new(3), 5);
+        assert_eq!(seq, vec![
+            vec![0.5, 0.5, 0.5],
+            vec![0.75, 0.25, 0.25],
+            vec![0.25, 0.75, 0.75],
+            vec![0.375, 0.375, 0.625],
+            vec![0.875, 0.875, 0.125],
+        ]);
+    }
+
+    #[test]
+    fn sobol_stratified() {
+        // each block of 2^k points hits every dyadic interval of length 2^-k exactly once
+        let mut seq = Sobol::new(SOBOL_MAX_BUILTIN_DIMENSION);
+        let mut point = vec![0.0; SOBOL_MAX_BUILTIN_DIMENSION];
+        let mut hits = vec![vec![0; 16]; SOBOL_MAX_BUILTIN_DIMENSION];
+        for _ in 0 .. 15 {
+            seq.next_point(&mut point);
+            for (axis, value) in point.iter().enumerate() {
+                hits[axis][(value * 16.0) as usize] += 1;
+            }
+        }
+        // zero point is skipped, so it is missing from the very first interval
+        for axis_hits in hits {
+            assert_eq!(axis_hits[0], 0);
+            assert!(axis_hits[1 ..].iter().all(|&count| count == 1));
+        }
+    }
+
+    #[test]
+    fn sampler_bounds() {
+        let bounds = Bounds::new(vec![-1.0, 10.0], vec![1.0, 20.0]);
+        let sequence = CranleyPatterson::new(Halton::with_dimension(2), vec![0.3, 0.9]);
+        let mut sampler = QuasiRandomSampler::new(sequence, bounds.clone());
+        for _ in 0 .. 100 {
+            assert!(bounds.contains(&sampler.sample()));
+        }
+    }
+}
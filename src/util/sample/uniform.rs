@@ -0,0 +1,34 @@
+use super::{Sampler, RandomSource};
+use util::space::Bounds;
+
+#[derive(Clone, Debug)]
+pub struct UniformSampler<R> {
+    bounds: Bounds,
+    rng: R,
+}
+
+impl<R> UniformSampler<R> where R: RandomSource {
+    pub fn new(bounds: Bounds, rng: R) -> UniformSampler<R> {
+        UniformSampler { bounds, rng, }
+    }
+
+    pub fn bounds(&self) -> &Bounds {
+        &self.bounds
+    }
+
+    pub fn rng_mut(&mut self) -> &mut R {
+        &mut self.rng
+    }
+}
+
+impl<R> Sampler for UniformSampler<R> where R: RandomSource {
+    type Sample = Vec<f64>;
+
+    fn sample(&mut self) -> Vec<f64> {
+        let rng = &mut self.rng;
+        let bounds = &self.bounds;
+        (0 .. bounds.dimension())
+            .map(|axis| bounds.lower[axis] + rng.next_unit() * bounds.extent(axis))
+            .collect()
+    }
+}
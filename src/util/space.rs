@@ -0,0 +1,83 @@
+// Bounds
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Bounds {
+    pub lower: Vec<f64>,
+    pub upper: Vec<f64>,
+}
+
+impl Bounds {
+    pub fn new(lower: Vec<f64>, upper: Vec<f64>) -> Bounds {
+        assert_eq!(lower.len(), upper.len(), "lower and upper bounds dimensions mismatch");
+        assert!(lower.iter().zip(upper.iter()).all(|(l, u)| l <= u), "lower bound exceeds upper bound");
+        Bounds { lower, upper, }
+    }
+
+    pub fn dimension(&self) -> usize {
+        self.lower.len()
+    }
+
+    pub fn extent(&self, axis: usize) -> f64 {
+        self.upper[axis] - self.lower[axis]
+    }
+
+    pub fn contains(&self, state: &[f64]) -> bool {
+        state.len() == self.dimension() &&
+            state.iter().enumerate().all(|(axis, &value)| value >= self.lower[axis] && value <= self.upper[axis])
+    }
+
+    pub fn from_unit(&self, unit: &[f64]) -> Vec<f64> {
+        unit.iter()
+            .enumerate()
+            .map(|(axis, &value)| self.lower[axis] + value * self.extent(axis))
+            .collect()
+    }
+}
+
+// Metric
+
+pub trait Metric<S> {
+    fn distance(&self, state_a: &S, state_b: &S) -> f64;
+}
+
+impl<S, F> Metric<S> for F where F: Fn(&S, &S) -> f64 {
+    fn distance(&self, state_a: &S, state_b: &S) -> f64 {
+        (self)(state_a, state_b)
+    }
+}
+
+pub trait Interpolation<S> {
+    // `t` runs from `0.0` at `from` to `1.0` at `to`
+    fn interpolate(&self, from: &S, to: &S, t: f64) -> S;
+}
+
+impl<S, F> Interpolation<S> for F where F: Fn(&S, &S, f64) -> S {
+    fn interpolate(&self, from: &S, to: &S, t: f64) -> S {
+        (self)(from, to, t)
+    }
+}
+
+// Euclidean
+
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub struct Euclidean;
+
+impl Metric<Vec<f64>> for Euclidean {
+    fn distance(&self, state_a: &Vec<f64>, state_b: &Vec<f64>) -> f64 {
+        euclidean_distance(state_a, state_b)
+    }
+}
+
+impl Interpolation<Vec<f64>> for Euclidean {
+    fn interpolate(&self, from: &Vec<f64>, to: &Vec<f64>, t: f64) -> Vec<f64> {
+        from.iter().zip(to.iter()).map(|(a, b)| a + (b - a) * t).collect()
+    }
+}
+
+pub fn euclidean_distance(state_a: &[f64], state_b: &[f64]) -> f64 {
+    state_a.iter()
+        .zip(state_b.iter())
+        .map(|(a, b)| (a - b) * (a - b))
+        .sum::<f64>()
+        .sqrt()
+}
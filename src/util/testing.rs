@@ -0,0 +1,113 @@
+// Deterministic fixtures shared by the unit tests.
+
+use budget::GoalDistance;
+use state::Problem;
+
+// xorshift64 generator of uniform values in `[0, 1)`
+pub fn xorshift(mut seed: u64) -> impl FnMut() -> f64 {
+    move || {
+        seed ^= seed << 13;
+        seed ^= seed >> 7;
+        seed ^= seed << 17;
+        (seed >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+// Integer line problem shared by the planner tests: the tree is a chain growing from 0 by one
+// unit per accepted sample, samples are the draw numbers 1, 2, 3, ...
+pub struct IntegerLine {
+    pub goal: Option<i32>,
+    pub reject: fn(i32) -> bool,
+    pub drawn: usize,
+    pub failing_draw: Option<usize>,
+    pub goal_check_failures: usize,
+}
+
+impl IntegerLine {
+    // accepts every sample, `None` is never reached
+    pub fn new(goal: Option<i32>) -> IntegerLine {
+        IntegerLine { goal, reject: |_| false, drawn: 0, failing_draw: None, goal_check_failures: 0, }
+    }
+
+    pub fn with_reject(mut self, reject: fn(i32) -> bool) -> IntegerLine {
+        self.reject = reject;
+        self
+    }
+
+    // the draw with this number fails once
+    pub fn with_failing_draw(mut self, draw: usize) -> IntegerLine {
+        self.failing_draw = Some(draw);
+        self
+    }
+
+    pub fn with_goal_check_failures(mut self, failures: usize) -> IntegerLine {
+        self.goal_check_failures = failures;
+        self
+    }
+}
+
+impl Problem for IntegerLine {
+    type EmptyRtt = ();
+    type Rtt = Vec<i32>;
+    type RttNodeRef = usize;
+    type Sample = i32;
+    type RttPath = Vec<i32>;
+    type Error = &'static str;
+
+    fn add_root(&mut self, (): ()) -> Result<Vec<i32>, ((), &'static str)> {
+        Ok(vec![0])
+    }
+
+    fn root_node(&mut self, _rtt: &mut Vec<i32>) -> Result<usize, &'static str> {
+        Ok(0)
+    }
+
+    fn goal_check(&mut self, rtt: &Vec<i32>, node_ref: &usize) -> Result<bool, &'static str> {
+        if self.goal_check_failures > 0 {
+            self.goal_check_failures -= 1;
+            return Err("goal check failure");
+        }
+        Ok(Some(rtt[*node_ref]) == self.goal)
+    }
+
+    fn build_path(&mut self, rtt: Vec<i32>, node_ref: usize) -> Result<Vec<i32>, ((Vec<i32>, usize), &'static str)> {
+        Ok(rtt[..= node_ref].to_vec())
+    }
+
+    fn prepare_sample(&mut self, _rtt: &mut Vec<i32>, _node_ref: usize) -> Result<(), (usize, &'static str)> {
+        Ok(())
+    }
+
+    fn sample(&mut self, _rtt: &mut Vec<i32>) -> Result<i32, &'static str> {
+        if self.failing_draw == Some(self.drawn + 1) {
+            self.failing_draw = None;
+            return Err("sampler failure");
+        }
+        self.drawn += 1;
+        Ok(self.drawn as i32)
+    }
+
+    fn closest_to_sample(&mut self, rtt: &mut Vec<i32>, _sample: &i32) -> Result<usize, &'static str> {
+        Ok(rtt.len() - 1)
+    }
+
+    fn transition_exists(&mut self, _rtt: &Vec<i32>, _node_ref: &usize, sample: &i32) -> bool {
+        !(self.reject)(*sample)
+    }
+
+    fn has_transition(&mut self, rtt: &mut Vec<i32>, node_ref: usize, _sample: i32) -> Result<usize, ((usize, i32), &'static str)> {
+        let next = rtt[node_ref] + 1;
+        rtt.push(next);
+        Ok(rtt.len() - 1)
+    }
+
+    fn no_transition(&mut self, _rtt: &mut Vec<i32>, _node_ref: usize) -> Result<(), (usize, &'static str)> {
+        Ok(())
+    }
+}
+
+impl GoalDistance for IntegerLine {
+    fn distance_to_goal(&mut self, rtt: &Vec<i32>, node_ref: &usize) -> f64 {
+        self.goal.map_or(f64::INFINITY, |goal| f64::from((goal - rtt[*node_ref]).abs()))
+    }
+}
@@ -0,0 +1,194 @@
+use std::collections::VecDeque;
+
+use {PlannerClosestNodeFound, PlannerRttNode, PlannerReadyToSample, TransHasTransition, TransNoTransition};
+use util::space::{Metric, Interpolation};
+
+pub trait StateValidityChecker<S> {
+    fn is_valid(&self, state: &S) -> bool;
+}
+
+impl<S, F> StateValidityChecker<S> for F where F: Fn(&S) -> bool {
+    fn is_valid(&self, state: &S) -> bool {
+        (self)(state)
+    }
+}
+
+// `from` state is assumed to be valid already
+pub trait MotionValidator<S> {
+    fn is_motion_valid(&self, from: &S, to: &S) -> bool;
+}
+
+impl<S, F> MotionValidator<S> for F where F: Fn(&S, &S) -> bool {
+    fn is_motion_valid(&self, from: &S, to: &S) -> bool {
+        (self)(from, to)
+    }
+}
+
+// DiscreteMotionValidator
+
+// Checks states along the motion sequentially with a step no longer than `resolution`.
+pub struct DiscreteMotionValidator<SP, VC> {
+    space: SP,
+    checker: VC,
+    resolution: f64,
+}
+
+impl<SP, VC> DiscreteMotionValidator<SP, VC> {
+    pub fn new(space: SP, checker: VC, resolution: f64) -> DiscreteMotionValidator<SP, VC> {
+        assert!(resolution > 0.0, "motion validator resolution should be positive");
+        DiscreteMotionValidator { space, checker, resolution, }
+    }
+
+    pub fn checker(&self) -> &VC {
+        &self.checker
+    }
+}
+
+impl<S, SP, VC> MotionValidator<S> for DiscreteMotionValidator<SP, VC>
+    where SP: Metric<S> + Interpolation<S>,
+          VC: StateValidityChecker<S>,
+{
+    fn is_motion_valid(&self, from: &S, to: &S) -> bool {
+        if !self.checker.is_valid(to) {
+            return false;
+        }
+        let steps = match segments_count(self.space.distance(from, to), self.resolution) {
+            Some(steps) => steps,
+            None => return false,
+        };
+        (1 .. steps).all(|step| {
+            let state = self.space.interpolate(from, to, step as f64 / steps as f64);
+            self.checker.is_valid(&state)
+        })
+    }
+}
+
+// SubdivisionMotionValidator
+
+// Checks the midpoint of the motion first and then recursively bisects both halves,
+// so obstacles in the middle of long motions are found early.
+pub struct SubdivisionMotionValidator<SP, VC> {
+    space: SP,
+    checker: VC,
+    resolution: f64,
+}
+
+impl<SP, VC> SubdivisionMotionValidator<SP, VC> {
+    pub fn new(space: SP, checker: VC, resolution: f64) -> SubdivisionMotionValidator<SP, VC> {
+        assert!(resolution > 0.0, "motion validator resolution should be positive");
+        SubdivisionMotionValidator { space, checker, resolution, }
+    }
+
+    pub fn checker(&self) -> &VC {
+        &self.checker
+    }
+}
+
+impl<S, SP, VC> MotionValidator<S> for SubdivisionMotionValidator<SP, VC>
+    where SP: Metric<S> + Interpolation<S>,
+          VC: StateValidityChecker<S>,
+{
+    fn is_motion_valid(&self, from: &S, to: &S) -> bool {
+        if !self.checker.is_valid(to) {
+            return false;
+        }
+        let steps = match segments_count(self.space.distance(from, to), self.resolution) {
+            Some(steps) => steps,
+            None => return false,
+        };
+        let mut queue = VecDeque::new();
+        queue.push_back((0, steps));
+        while let Some((lo, hi)) = queue.pop_front() {
+            if hi - lo < 2 {
+                continue;
+            }
+            let mid = (lo + hi) / 2;
+            let state = self.space.interpolate(from, to, mid as f64 / steps as f64);
+            if !self.checker.is_valid(&state) {
+                return false;
+            }
+            queue.push_back((lo, mid));
+            queue.push_back((mid, hi));
+        }
+        true
+    }
+}
+
+// `None` for a motion of non-finite length, which is never valid
+fn segments_count(distance: f64, resolution: f64) -> Option<usize> {
+    if distance.is_finite() {
+        Some(((distance / resolution).ceil() as usize).max(1))
+    } else {
+        None
+    }
+}
+
+// Transition
+
+pub enum Transition<RT, NR> {
+    Valid(PlannerRttNode<RT, NR>),
+    Invalid(PlannerReadyToSample<RT>),
+}
+
+// Chooses between `has_transition` and `no_transition` by validating the motion
+// from the closest node state (extracted with `node_state`) to the sample.
+pub fn decide_transition<RT, NR, S, MV, F, TH, TN>(
+    planner: PlannerClosestNodeFound<RT, NR, S>,
+    validator: &MV,
+    node_state: F,
+    has_trans: TH,
+    no_trans: TN,
+) ->
+    Result<Transition<RT, TH::RttNodeRef>, TH::Error>
+    where MV: MotionValidator<S>,
+          F: for<'a> FnOnce(&'a RT, &'a NR) -> &'a S,
+          TH: TransHasTransition<RT, NR, S>,
+          TN: TransNoTransition<RT, NR, Error = TH::Error>,
+{
+    let valid = validator.is_motion_valid(node_state(planner.rtt(), planner.node_ref()), planner.sample());
+    if valid {
+        Ok(Transition::Valid(planner.has_transition(has_trans)?))
+    } else {
+        Ok(Transition::Invalid(planner.no_transition(no_trans)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use super::*;
+    use util::space::Euclidean;
+
+    #[test]
+    fn discrete_and_subdivision() {
+        let checked = RefCell::new(Vec::new());
+        let checker = |state: &Vec<f64>| {
+            checked.borrow_mut().push(state[0]);
+            state[0] < 0.25 || state[0] > 0.45
+        };
+
+        let discrete = DiscreteMotionValidator::new(Euclidean, &checker, 0.1);
+        assert!(discrete.is_motion_valid(&vec![0.0], &vec![0.2]));
+        assert!(!discrete.is_motion_valid(&vec![0.0], &vec![1.0]));
+        assert!(discrete.is_motion_valid(&vec![0.5], &vec![1.0]));
+
+        let subdivision = SubdivisionMotionValidator::new(Euclidean, &checker, 0.1);
+        checked.borrow_mut().clear();
+        assert!(!subdivision.is_motion_valid(&vec![0.0], &vec![0.7]));
+        assert_eq!(checked.borrow().len(), 2);
+        assert!((checked.borrow()[1] - 0.3).abs() < 1e-9);
+        checked.borrow_mut().clear();
+        assert!(subdivision.is_motion_valid(&vec![0.0], &vec![0.22]));
+        assert_eq!(checked.borrow().len(), 3);
+    }
+
+    #[test]
+    fn infinite_motion() {
+        let free = |_state: &Vec<f64>| true;
+        let discrete = DiscreteMotionValidator::new(Euclidean, &free, 0.1);
+        assert!(!discrete.is_motion_valid(&vec![0.0], &vec![f64::INFINITY]));
+        assert!(!discrete.is_motion_valid(&vec![0.0], &vec![f64::NAN]));
+        let subdivision = SubdivisionMotionValidator::new(Euclidean, &free, 0.1);
+        assert!(!subdivision.is_motion_valid(&vec![f64::NEG_INFINITY], &vec![0.0]));
+    }
+}